@@ -0,0 +1,134 @@
+use macroquad::audio::{
+    load_sound, play_sound, set_sound_volume, stop_sound, PlaySoundParams, Sound,
+};
+
+use gmtk_jam_2021::game_data::GameData;
+use gmtk_jam_2021::settings::SettingsData;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum SoundEvent {
+    ConnectionStart,
+    Snap,
+    Connect,
+    Blocked,
+    PortalJump,
+    Undo,
+    Win,
+}
+
+// sound effects of the game events and music of the packs,
+// files that are not loaded are reported and stay silent
+pub struct Audio {
+    sounds: Vec<(SoundEvent, Sound)>,
+    music: Vec<(String, Sound)>,
+    current_music: Option<(String, Sound)>,
+}
+
+impl Audio {
+    pub async fn load(
+        game_data: &GameData,
+        game_data_file_name: &str,
+        data_errors: &mut Vec<String>,
+    ) -> Audio {
+        let mut audio = Audio {
+            sounds: Vec::new(),
+            music: Vec::new(),
+            current_music: None,
+        };
+        let sounds_data = game_data.get_sound_file_names(game_data_file_name);
+        let sound_file_names = [
+            (SoundEvent::ConnectionStart, sounds_data.connection_start),
+            (SoundEvent::Snap, sounds_data.snap),
+            (SoundEvent::Connect, sounds_data.connect),
+            (SoundEvent::Blocked, sounds_data.blocked),
+            (SoundEvent::PortalJump, sounds_data.portal_jump),
+            (SoundEvent::Undo, sounds_data.undo),
+            (SoundEvent::Win, sounds_data.win),
+        ];
+        for (event, file_name) in sound_file_names {
+            if let Some(file_name) = file_name {
+                if let Some(sound) = load_audio_file(&file_name, data_errors).await {
+                    audio.sounds.push((event, sound));
+                }
+            }
+        }
+        for chapter_data in &game_data.chapters {
+            if let Some(file_name) = &chapter_data.music_file_name {
+                if audio.music.iter().any(|(name, _)| name == file_name) {
+                    continue;
+                }
+                if let Some(sound) = load_audio_file(file_name, data_errors).await {
+                    audio.music.push((file_name.clone(), sound));
+                }
+            }
+        }
+        audio
+    }
+
+    pub fn play(&self, event: SoundEvent, settings_data: &SettingsData) {
+        let volume = settings_data.get_sound_volume();
+        if volume <= 0.0 {
+            return;
+        }
+        for (sound_event, sound) in &self.sounds {
+            if *sound_event == event {
+                play_sound(
+                    *sound,
+                    PlaySoundParams {
+                        looped: false,
+                        volume,
+                    },
+                );
+            }
+        }
+    }
+
+    // keeps the music that is already playing, None stops it
+    pub fn play_music(&mut self, file_name: Option<&str>, settings_data: &SettingsData) {
+        let current_file_name = self.current_music.as_ref().map(|(name, _)| name.as_str());
+        if current_file_name == file_name {
+            return;
+        }
+        if let Some((_, sound)) = self.current_music.take() {
+            stop_sound(sound);
+        }
+        let sound = file_name.and_then(|file_name| {
+            self.music
+                .iter()
+                .find(|(name, _)| name == file_name)
+                .map(|(_, sound)| *sound)
+        });
+        if let (Some(file_name), Some(sound)) = (file_name, sound) {
+            play_sound(
+                sound,
+                PlaySoundParams {
+                    looped: true,
+                    volume: settings_data.get_music_volume(),
+                },
+            );
+            self.current_music = Some((file_name.to_string(), sound));
+        }
+    }
+
+    pub fn get_music_file_name(&self) -> Option<&str> {
+        self.current_music.as_ref().map(|(name, _)| name.as_str())
+    }
+
+    pub fn apply_settings(&self, settings_data: &SettingsData) {
+        if let Some((_, sound)) = &self.current_music {
+            set_sound_volume(*sound, settings_data.get_music_volume());
+        }
+    }
+}
+
+async fn load_audio_file(file_name: &str, data_errors: &mut Vec<String>) -> Option<Sound> {
+    match load_sound(file_name).await {
+        Ok(sound) => Some(sound),
+        Err(error) => {
+            let error = format!("Sound '{}' not loaded: {}", file_name, error);
+            println!("{}", error);
+            data_errors.push(error);
+            None
+        }
+    }
+}
@@ -0,0 +1,101 @@
+use std::process::exit;
+
+use gmtk_jam_2021::generator::{self, GeneratorData};
+
+// Generates solvable levels into a level pack file without opening a window:
+//     generate_levels generated.data --count 5 --seed 7 --layouts 2 --size 6x4 --pairs 4
+// The pack can be listed in the manifest or edited as a starting point of new levels.
+
+const USAGE: &str = "\
+usage: generate_levels <output file> [options]
+    --count <number>          levels to generate, 1 by default
+    --seed <number>           seed of the first level
+    --layouts <number>        layouts of every level
+    --size <width>x<height>   cells of every layout
+    --pairs <number>          pairs of points, up to 10
+    --obstacles <share>       share of the free cells with obstacles, from 0 up to 1
+    --win-count <number>      connections needed before the finish
+    --max-solutions <number>  the most solutions a level may have";
+
+fn parse_args(args: &[String]) -> Result<(String, usize, GeneratorData), String> {
+    let mut args = args.iter().skip(1);
+    let file_name = match args.next() {
+        Some(file_name) if !file_name.starts_with("--") => file_name.clone(),
+        _ => return Err(USAGE.to_string()),
+    };
+    let mut count = 1;
+    let mut generator_data = GeneratorData::default();
+    while let Some(arg) = args.next() {
+        let value = args
+            .next()
+            .ok_or_else(|| format!("missing value for '{}'", arg))?;
+        let wrong_value = || format!("wrong value '{}' for '{}'", value, arg);
+        let parse_number = || value.parse::<usize>().map_err(|_| wrong_value());
+        match arg.as_str() {
+            "--count" => count = parse_number()?,
+            "--seed" => generator_data.seed = value.parse().map_err(|_| wrong_value())?,
+            "--layouts" => generator_data.layouts_count = parse_number()?,
+            "--size" => {
+                let mut numbers = value.split('x').map(|number| number.parse::<usize>());
+                generator_data.size = match (numbers.next(), numbers.next(), numbers.next()) {
+                    (Some(Ok(width)), Some(Ok(height)), None) => (width, height),
+                    _ => return Err(wrong_value()),
+                };
+            }
+            "--pairs" => generator_data.pairs_count = parse_number()?,
+            "--obstacles" => {
+                generator_data.obstacle_density = value.parse().map_err(|_| wrong_value())?
+            }
+            "--win-count" => generator_data.win_count = parse_number()?,
+            "--max-solutions" => generator_data.max_solutions = parse_number()?,
+            _ => return Err(format!("unknown argument '{}'\n{}", arg, USAGE)),
+        }
+    }
+    Ok((file_name, count, generator_data))
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    let (file_name, count, mut generator_data) = match parse_args(&args) {
+        Ok(parsed) => parsed,
+        Err(error) => {
+            eprintln!("{}", error);
+            exit(2);
+        }
+    };
+
+    let mut levels = Vec::new();
+    for _ in 0..count {
+        match generator::generate_level(&generator_data) {
+            Ok(generated_data) => {
+                let solutions_data = &generated_data.solutions_data;
+                println!(
+                    "{}: {} solution(s), the shortest takes {} connections",
+                    generated_data.level_data.name,
+                    solutions_data.solutions_count,
+                    solutions_data
+                        .shortest_solution
+                        .as_ref()
+                        .map_or(0, |solution| solution.len())
+                );
+                // the next level starts from the seed after the accepted one
+                generator_data.seed = generated_data.seed.wrapping_add(1);
+                levels.push(generated_data.level_data);
+            }
+            Err(error) => {
+                eprintln!("{}", error);
+                exit(1);
+            }
+        }
+    }
+
+    let result = generator::export_levels(levels)
+        .and_then(|text| std::fs::write(&file_name, text).map_err(|error| error.to_string()));
+    match result {
+        Ok(()) => println!("saved to {}", file_name),
+        Err(error) => {
+            eprintln!("{}", error);
+            exit(2);
+        }
+    }
+}
@@ -0,0 +1,120 @@
+use std::process::exit;
+
+use gmtk_jam_2021::game_data::{ConnectionData, GameData, LevelAdditionalData};
+use gmtk_jam_2021::replay::{ReplayActionType, ReplayData};
+use gmtk_jam_2021::score::ScoreData;
+use gmtk_jam_2021::simulation;
+use gmtk_jam_2021::spatial_grid::ConnectionsGrid;
+
+// Re-simulates recorded replays without opening a window:
+//     verify_replay assets/game.data replays/5_1626000000.replay [more replays...]
+// Exits with 1 if any recorded step is not accepted by the current game logic.
+
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    if args.len() < 3 {
+        eprintln!("usage: {} <game data file> <replay file>...", args[0]);
+        exit(2);
+    }
+
+    let game_data = match GameData::load_from_file_blocking(&args[1]) {
+        Ok(game_data) => game_data,
+        Err(error) => {
+            eprintln!("{}", error);
+            exit(2);
+        }
+    };
+
+    let mut is_all_accepted = true;
+    for file_name in &args[2..] {
+        match ReplayData::load_from_file(file_name) {
+            Ok(replay_data) => {
+                println!("replay: {}", file_name);
+                is_all_accepted &= verify_replay(&game_data, &replay_data);
+            }
+            Err(error) => {
+                eprintln!("{}", error);
+                exit(2);
+            }
+        }
+    }
+
+    if !is_all_accepted {
+        exit(1);
+    }
+}
+
+fn verify_replay(game_data: &GameData, replay_data: &ReplayData) -> bool {
+    let level_index = match replay_data.find_level_index(game_data) {
+        Some(level_index) => level_index,
+        None => {
+            println!("  level '{}' not found", replay_data.level_name);
+            return false;
+        }
+    };
+    let level_data = &game_data.levels[level_index];
+    println!("level: {}. {}", level_index + 1, level_data.name);
+
+    let level_additional_data = LevelAdditionalData::new(level_data);
+    let mut connections_data = Vec::<ConnectionData>::new();
+    let mut connections_grid = ConnectionsGrid::default();
+    let mut is_all_accepted = true;
+    let mut undos_count = 0;
+    for (index, action) in replay_data.actions.iter().enumerate() {
+        let is_accepted = simulation::simulate_action(
+            level_data,
+            &level_additional_data,
+            &mut connections_data,
+            &mut connections_grid,
+            &action.action_type,
+        );
+        is_all_accepted &= is_accepted;
+        if is_accepted && matches!(action.action_type, ReplayActionType::Undo { .. }) {
+            undos_count += 1;
+        }
+        let description = match action.action_type {
+            ReplayActionType::Connect {
+                layout_index,
+                from_point_index,
+                to_point_index,
+            } => format!(
+                "connect layout {}: {} -> {}",
+                layout_index, from_point_index, to_point_index
+            ),
+            ReplayActionType::Undo { connection_index } => {
+                format!("undo to {} connections", connection_index)
+            }
+        };
+        println!(
+            "  {:>3}. {:>8.2}s {:<32} {}",
+            index + 1,
+            action.time,
+            description,
+            if is_accepted { "accepted" } else { "REJECTED" }
+        );
+    }
+
+    println!("connections:");
+    for connection_data in &connections_data {
+        println!(
+            "  layout {}: {} -> {}",
+            connection_data.layout_index,
+            connection_data.from_point_index,
+            connection_data.to_point_index
+        );
+    }
+    if simulation::is_win(&level_additional_data, &connections_data) {
+        let score_data = ScoreData::new(&level_additional_data, &connections_data, undos_count);
+        println!(
+            "won: yes, score: {} (length {:.1}, {} connections, {} portal jumps, {} undos)\n",
+            score_data.get_score(),
+            score_data.length,
+            score_data.connections_count,
+            score_data.portal_jumps_count,
+            score_data.undos_count
+        );
+    } else {
+        println!("won: no\n");
+    }
+    is_all_accepted
+}
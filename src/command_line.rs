@@ -0,0 +1,95 @@
+use gmtk_jam_2021::game_data::GameData;
+
+pub const DEFAULT_DATA_FILE_NAME: &str = "assets/game.data";
+
+pub const USAGE: &str = "\
+usage: gmtk_jam_2021 [--data <file>] [--level <number or name>] [--layout <number>]
+    --data <file>      game data file to load instead of assets/game.data
+    --level <level>    start the level right away, by its number in the menu or by its name
+    --layout <number>  start the level in this layout instead of the one with the start point
+    --help             print this message";
+
+pub struct CommandLineArgs {
+    pub data_file_name: String,
+    pub level: Option<String>,
+    pub layout_number: Option<usize>,
+    pub is_help: bool, // the usage is printed instead of starting the game
+}
+
+impl CommandLineArgs {
+    pub fn parse(args: impl Iterator<Item = String>) -> Result<CommandLineArgs, String> {
+        let mut command_line_args = CommandLineArgs {
+            data_file_name: DEFAULT_DATA_FILE_NAME.to_string(),
+            level: None,
+            layout_number: None,
+            is_help: false,
+        };
+        let mut args = args.skip(1);
+        while let Some(arg) = args.next() {
+            let mut get_value = || {
+                args.next()
+                    .ok_or_else(|| format!("missing value for '{}'", arg))
+            };
+            match arg.as_str() {
+                "--data" => command_line_args.data_file_name = get_value()?,
+                "--level" => command_line_args.level = Some(get_value()?),
+                "--layout" => {
+                    let value = get_value()?;
+                    let layout_number = value
+                        .parse::<usize>()
+                        .ok()
+                        .filter(|number| 0 < *number)
+                        .ok_or_else(|| format!("wrong layout number '{}'", value))?;
+                    command_line_args.layout_number = Some(layout_number);
+                }
+                "--help" | "-h" => {
+                    command_line_args.is_help = true;
+                    return Ok(command_line_args);
+                }
+                _ => return Err(format!("unknown argument '{}'\n{}", arg, USAGE)),
+            }
+        }
+        if command_line_args.layout_number.is_some() && command_line_args.level.is_none() {
+            return Err(format!("'--layout' requires '--level'\n{}", USAGE));
+        }
+        Ok(command_line_args)
+    }
+
+    // (level index, layout index) to start with, None to start from the main menu
+    pub fn get_start_level(
+        &self,
+        game_data: &GameData,
+    ) -> Result<Option<(usize, Option<usize>)>, String> {
+        let level = match &self.level {
+            Some(level) => level,
+            None => return Ok(None),
+        };
+        let level_index = match level.parse::<usize>() {
+            Ok(number) if 0 < number && number <= game_data.levels.len() => number - 1,
+            Ok(number) => return Err(format!("there is no level number {}", number)),
+            Err(_) => game_data
+                .levels
+                .iter()
+                .position(|level_data| level_data.name == *level)
+                .or_else(|| {
+                    game_data
+                        .levels
+                        .iter()
+                        .position(|level_data| level_data.name.eq_ignore_ascii_case(level))
+                })
+                .ok_or_else(|| format!("there is no level named '{}'", level))?,
+        };
+        let layouts_count = game_data.levels[level_index].layouts.len();
+        let layout_index = match self.layout_number {
+            Some(number) if number <= layouts_count => Some(number - 1),
+            Some(number) => {
+                return Err(format!(
+                    "level '{}' has {} layouts, there is no layout number {}",
+                    game_data.levels[level_index].name, layouts_count, number
+                ))
+            }
+            None => None,
+        };
+        Ok(Some((level_index, layout_index)))
+    }
+}
@@ -0,0 +1,43 @@
+use std::time::SystemTime;
+
+pub const CHECK_INTERVAL: f64 = 0.5; // seconds
+
+// polls modification times of the data files, native builds only
+pub struct DataWatcher {
+    modified_times: Vec<(String, Option<SystemTime>)>,
+    last_check_time: f64,
+}
+
+impl DataWatcher {
+    pub fn new(file_names: &[String], time: f64) -> DataWatcher {
+        DataWatcher {
+            modified_times: file_names
+                .iter()
+                .map(|file_name| (file_name.clone(), get_modified_time(file_name)))
+                .collect(),
+            last_check_time: time,
+        }
+    }
+
+    pub fn is_changed(&mut self, time: f64) -> bool {
+        if cfg!(target_arch = "wasm32") || time - self.last_check_time < CHECK_INTERVAL {
+            return false;
+        }
+        self.last_check_time = time;
+        let mut is_changed = false;
+        for (file_name, modified_time) in self.modified_times.iter_mut() {
+            let new_modified_time = get_modified_time(file_name);
+            if *modified_time != new_modified_time {
+                *modified_time = new_modified_time;
+                is_changed = true;
+            }
+        }
+        is_changed
+    }
+}
+
+fn get_modified_time(file_name: &str) -> Option<SystemTime> {
+    std::fs::metadata(file_name)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
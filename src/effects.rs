@@ -0,0 +1,240 @@
+use macroquad::prelude::*;
+
+pub const LINE_DRAW_DURATION: f32 = 0.25;
+pub const PULSE_DURATION: f32 = 0.3;
+pub const SHAKE_DURATION: f32 = 0.3;
+pub const SHAKE_STRENGTH: f32 = 0.02; // in screen units
+pub const PARTICLE_GRAVITY: f32 = 6.0;
+pub const BLOCKED_PARTICLES_COUNT: usize = 12;
+pub const WIN_PARTICLES_COUNT: usize = 80;
+
+// positions are in the coordinates of the layout the effect belongs to
+struct Particle {
+    layout_index: usize,
+    position: Vec2,
+    velocity: Vec2,
+    color: Color,
+    start_time: f32,
+    life: f32,
+}
+
+struct LineTween {
+    layout_index: usize,
+    from: Vec2,
+    to: Vec2,
+    color: Color,
+    start_time: f32,
+}
+
+struct Pulse {
+    layout_index: usize,
+    position: Vec2,
+    color: Color,
+    start_time: f32,
+}
+
+// short feedback on connection events, with reduced motion nothing moves or shakes
+#[derive(Default)]
+pub struct Effects {
+    pub is_reduced_motion: bool,
+    particles: Vec<Particle>,
+    line_tweens: Vec<LineTween>,
+    pulses: Vec<Pulse>,
+    shake_start_time: Option<f32>,
+    snap_position: Option<Vec2>,
+}
+
+impl Effects {
+    pub fn clear(&mut self) {
+        self.particles.clear();
+        self.line_tweens.clear();
+        self.pulses.clear();
+        self.shake_start_time = None;
+        self.snap_position = None;
+    }
+
+    pub fn add_connection(
+        &mut self,
+        layout_index: usize,
+        from: Vec2,
+        to: Vec2,
+        color: Color,
+        time: f32,
+    ) {
+        if !self.is_reduced_motion {
+            self.line_tweens.push(LineTween {
+                layout_index,
+                from,
+                to,
+                color,
+                start_time: time,
+            });
+        }
+        self.add_pulse(layout_index, to, color, time);
+    }
+
+    pub fn add_pulse(&mut self, layout_index: usize, position: Vec2, color: Color, time: f32) {
+        self.pulses.push(Pulse {
+            layout_index,
+            position,
+            color,
+            start_time: time,
+        });
+    }
+
+    // pulses once when the aim snaps to a new point, returns true then
+    pub fn update_snap(
+        &mut self,
+        layout_index: usize,
+        snap_position: Option<Vec2>,
+        color: Color,
+        time: f32,
+    ) -> bool {
+        let is_new_snap = snap_position.is_some() && self.snap_position != snap_position;
+        if let Some(position) = snap_position.filter(|_| is_new_snap) {
+            self.add_pulse(layout_index, position, color, time);
+        }
+        self.snap_position = snap_position;
+        is_new_snap
+    }
+
+    pub fn add_blocked(&mut self, layout_index: usize, position: Vec2, color: Color, time: f32) {
+        if self.is_reduced_motion {
+            self.add_pulse(layout_index, position, color, time);
+            return;
+        }
+        self.shake_start_time = Some(time);
+        self.add_burst(
+            layout_index,
+            position,
+            color,
+            BLOCKED_PARTICLES_COUNT,
+            2.0,
+            time,
+        );
+    }
+
+    pub fn add_win(&mut self, layout_index: usize, position: Vec2, colors: &[Color], time: f32) {
+        if self.is_reduced_motion {
+            for color in colors {
+                self.add_pulse(layout_index, position, *color, time);
+            }
+            return;
+        }
+        for (index, color) in colors.iter().enumerate() {
+            let count = WIN_PARTICLES_COUNT / colors.len().max(1);
+            self.add_burst(
+                layout_index,
+                position,
+                *color,
+                count,
+                4.0 + index as f32,
+                time,
+            );
+        }
+    }
+
+    fn add_burst(
+        &mut self,
+        layout_index: usize,
+        position: Vec2,
+        color: Color,
+        count: usize,
+        speed: f32,
+        time: f32,
+    ) {
+        for _ in 0..count {
+            let angle = rand::gen_range(0.0, std::f32::consts::TAU);
+            let velocity = vec2(angle.cos(), angle.sin()) * speed * rand::gen_range(0.3, 1.0);
+            self.particles.push(Particle {
+                layout_index,
+                position,
+                velocity,
+                color,
+                start_time: time,
+                life: rand::gen_range(0.4, 1.0),
+            });
+        }
+    }
+
+    // camera offset in screen units, zero when nothing shakes
+    pub fn get_shake_offset(&self, time: f32) -> Vec2 {
+        match self.shake_start_time {
+            Some(start_time) if time - start_time < SHAKE_DURATION => {
+                let strength = SHAKE_STRENGTH * (1.0 - (time - start_time) / SHAKE_DURATION);
+                vec2(
+                    rand::gen_range(-strength, strength),
+                    rand::gen_range(-strength, strength),
+                )
+            }
+            _ => Vec2::ZERO,
+        }
+    }
+
+    pub fn update(&mut self, time: f32, delta_time: f32) {
+        for particle in self.particles.iter_mut() {
+            particle.velocity.y += PARTICLE_GRAVITY * delta_time;
+            particle.position += particle.velocity * delta_time;
+        }
+        self.particles
+            .retain(|particle| time - particle.start_time < particle.life);
+        self.line_tweens
+            .retain(|line_tween| time - line_tween.start_time < LINE_DRAW_DURATION);
+        self.pulses
+            .retain(|pulse| time - pulse.start_time < PULSE_DURATION);
+        if self
+            .shake_start_time
+            .is_some_and(|start_time| SHAKE_DURATION <= time - start_time)
+        {
+            self.shake_start_time = None;
+        }
+    }
+
+    // draws the effects of one layout with the camera of this layout
+    pub fn draw(&self, layout_index: usize, time: f32, thickness: f32) {
+        for line_tween in &self.line_tweens {
+            if line_tween.layout_index == layout_index {
+                let t = ((time - line_tween.start_time) / LINE_DRAW_DURATION).clamp(0.0, 1.0);
+                let to = line_tween.from.lerp(line_tween.to, t);
+                draw_line(
+                    line_tween.from.x,
+                    line_tween.from.y,
+                    to.x,
+                    to.y,
+                    thickness * 2.0,
+                    line_tween.color,
+                );
+            }
+        }
+        for pulse in &self.pulses {
+            if pulse.layout_index == layout_index {
+                let t = ((time - pulse.start_time) / PULSE_DURATION).clamp(0.0, 1.0);
+                let color = Color::new(pulse.color.r, pulse.color.g, pulse.color.b, 1.0 - t);
+                let radius = if self.is_reduced_motion {
+                    0.4
+                } else {
+                    0.25 + 0.3 * t
+                };
+                draw_circle_lines(
+                    pulse.position.x,
+                    pulse.position.y,
+                    radius,
+                    thickness / 2.0,
+                    color,
+                );
+            }
+        }
+        for particle in &self.particles {
+            if particle.layout_index == layout_index {
+                let t = (time - particle.start_time) / particle.life;
+                let color = Color::new(
+                    particle.color.r,
+                    particle.color.g,
+                    particle.color.b,
+                    1.0 - t,
+                );
+                draw_circle(particle.position.x, particle.position.y, 0.06, color);
+            }
+        }
+    }
+}
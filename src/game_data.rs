@@ -1,61 +1,658 @@
+use std::collections::HashMap;
 use std::fs::File;
 
 use macroquad::prelude::*;
 use parry2d::math::{Isometry, Real};
+use parry2d::na::Point2;
 use parry2d::shape::{Ball, Segment};
 use ron::ser::PrettyConfig;
 use serde::{Deserialize, Serialize};
 
+use crate::generator::GeneratorData;
+use crate::solver;
+use crate::spatial_grid::SpatialGrid;
+
 #[derive(Default, Serialize, Deserialize)]
 pub struct GameData {
+    // size of the virtual screen, its aspect ratio is kept
+    // by ScalingPolicy::Letterbox and ScalingPolicy::Stretch
     pub resolution: (f32, f32),
+    #[serde(default)]
+    pub screen: ScreenData,
+    #[serde(default)]
+    pub window: WindowData,
+    #[serde(default)]
+    pub levels: Vec<LevelData>,
+    #[serde(default)]
+    pub packs: Vec<PackData>,
+    // theme files relative to the main data file, the first one is used by default
+    #[serde(default)]
+    pub themes: Vec<String>,
+    #[serde(default)]
+    pub sounds: SoundsData,
+    // the parameters of the levels of the endless mode, the seed is picked when it starts
+    #[serde(default)]
+    pub endless: GeneratorData,
+    // levels of every pack are appended to `levels`, chapters keep the boundaries
+    #[serde(skip)]
+    pub chapters: Vec<ChapterData>,
+    // every file and directory the data was loaded from, to watch for changes
+    #[serde(skip)]
+    pub file_names: Vec<String>,
+    // problems with pack files, the data is still usable without their levels
+    #[serde(skip)]
+    pub errors: Vec<String>,
+    // problems of the loaded levels, the data is still valid
+    #[serde(skip)]
+    pub warnings: Vec<String>,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScreenData {
+    pub scaling_policy: ScalingPolicy,
+    pub min_margin: f32, // in layout cells, around the layout
+}
+
+#[derive(Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum ScalingPolicy {
+    // the layout fits the window, the rest of the window shows more of the world
+    #[default]
+    Expand,
+    // the virtual screen fits the window, the rest of the window is covered by bars
+    Letterbox,
+    // the virtual screen fills the window, the layout is scaled non-uniformly
+    Stretch,
+}
+
+// the window is created before the game starts, so only the main data file is used for it
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct WindowData {
+    pub title: String,
+    pub size: (i32, i32),
+    pub high_dpi: bool,
+    pub fullscreen: bool,
+}
+
+impl Default for WindowData {
+    fn default() -> Self {
+        WindowData {
+            title: "GMTK Game Jam 2021".to_string(),
+            size: (800, 600),
+            high_dpi: false,
+            fullscreen: false,
+        }
+    }
+}
+
+impl WindowData {
+    pub fn to_conf(&self) -> Conf {
+        Conf {
+            window_title: self.title.clone(),
+            window_width: self.size.0,
+            window_height: self.size.1,
+            high_dpi: self.high_dpi,
+            fullscreen: self.fullscreen,
+            ..Default::default()
+        }
+    }
+}
+
+// sound files relative to the main data file, an event without a file is silent
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SoundsData {
+    pub connection_start: Option<String>,
+    pub snap: Option<String>,
+    pub connect: Option<String>,
+    pub blocked: Option<String>,
+    pub portal_jump: Option<String>,
+    pub undo: Option<String>,
+    pub win: Option<String>,
+}
+
+// an entry of the manifest: files or directories of *.data files with levels
+#[derive(Clone, Serialize, Deserialize)]
+pub struct PackData {
+    pub title: String,
+    #[serde(default)]
+    pub order: i32,
+    #[serde(default)]
+    pub unlock_requirement: UnlockRequirement,
+    pub paths: Vec<String>,
+    // played in a loop while a level of the pack is open
+    #[serde(default)]
+    pub music: Option<String>,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+pub struct PackFileData {
     pub levels: Vec<LevelData>,
 }
 
+#[derive(Default, Clone, PartialEq, Serialize, Deserialize)]
+pub enum UnlockRequirement {
+    #[default]
+    Always,
+    CompletedLevels(usize),
+    CompletedPack(String),
+}
+
+pub struct ChapterData {
+    pub title: String,
+    pub unlock_requirement: UnlockRequirement,
+    pub first_level_index: usize,
+    pub levels_count: usize,
+    pub music_file_name: Option<String>,
+}
+
+impl ChapterData {
+    pub fn get_level_indices(&self) -> std::ops::Range<usize> {
+        self.first_level_index..self.first_level_index + self.levels_count
+    }
+}
+
+// content or reading error of every pack file by its name
+type PackFiles = HashMap<String, Result<Vec<u8>, String>>;
+
+pub const PACK_FILE_EXTENSION: &str = "data";
+pub const DEFAULT_CHAPTER_TITLE: &str = "Levels";
+// the paths the solver tries while checking the levels with max_moves
+const MAX_CHECK_STEPS: usize = 100_000;
+
 impl GameData {
-    pub async fn load_from_file(file_name: &str) -> Result<GameData, &str> {
+    pub fn from_bytes(bytes: &[u8]) -> Result<GameData, String> {
+        ron::de::from_bytes(bytes).map_err(|error| format!("Game data parsing failed: {}", error))
+    }
+
+    pub async fn load_from_file(file_name: &str) -> Result<GameData, String> {
         let file_result = load_file(file_name).await;
         match file_result {
             Ok(file) => {
-                let game_data: GameData =
-                    ron::de::from_bytes(file.as_ref()).expect("Deserialization from file failed");
+                let mut game_data = GameData::from_bytes(file.as_ref())?;
+                let packs_file_names = game_data.get_packs_file_names(file_name);
+                let mut packs_files = PackFiles::new();
+                for (_, pack_file_names) in &packs_file_names {
+                    for pack_file_name in pack_file_names {
+                        let bytes = load_file(pack_file_name)
+                            .await
+                            .map_err(|error| error.to_string());
+                        packs_files.insert(pack_file_name.clone(), bytes);
+                    }
+                }
+                game_data.build_chapters(file_name, packs_file_names, packs_files);
                 Ok(game_data)
             }
             Err(_) => {
                 let pretty_config = PrettyConfig::new().with_separate_tuple_members(true);
                 // TODO: do not use FILE at WebGL version
                 let new_file = File::create(file_name).expect("file not created");
-                let new_game_data = GameData::default();
+                let mut new_game_data = GameData::default();
                 ron::ser::to_writer_pretty(new_file, &new_game_data, pretty_config)
                     .expect("Serialization to new file failed");
+                new_game_data.build_chapters(file_name, vec![], PackFiles::new());
                 Ok(new_game_data)
             }
         }
     }
+
+    // the same as load_from_file, for tools that run without macroquad's event loop
+    pub fn load_from_file_blocking(file_name: &str) -> Result<GameData, String> {
+        let bytes = std::fs::read(file_name)
+            .map_err(|error| format!("Game data '{}' not read: {}", file_name, error))?;
+        let mut game_data = GameData::from_bytes(&bytes)?;
+        let packs_file_names = game_data.get_packs_file_names(file_name);
+        let mut packs_files = PackFiles::new();
+        for (_, pack_file_names) in &packs_file_names {
+            for pack_file_name in pack_file_names {
+                let bytes = std::fs::read(pack_file_name).map_err(|error| error.to_string());
+                packs_files.insert(pack_file_name.clone(), bytes);
+            }
+        }
+        game_data.build_chapters(file_name, packs_file_names, packs_files);
+        Ok(game_data)
+    }
+
+    // the files of every pack by the order of the packs, the loaders read them
+    fn get_packs_file_names(&self, file_name: &str) -> Vec<(PackData, Vec<String>)> {
+        let mut packs = self.packs.clone();
+        packs.sort_by_key(|pack_data| pack_data.order);
+        packs
+            .into_iter()
+            .map(|pack_data| {
+                let pack_file_names = get_pack_file_names(file_name, &pack_data);
+                (pack_data, pack_file_names)
+            })
+            .collect()
+    }
+
+    // levels written right in the main file form the first chapter
+    fn build_chapters(
+        &mut self,
+        file_name: &str,
+        packs_file_names: Vec<(PackData, Vec<String>)>,
+        packs_files: PackFiles,
+    ) {
+        self.chapters.clear();
+        self.errors.clear();
+        self.warnings.clear();
+        self.file_names = vec![file_name.to_string()];
+        for pack_data in &self.packs {
+            for path in &pack_data.paths {
+                self.file_names.push(get_pack_path(file_name, path));
+            }
+        }
+        if !self.levels.is_empty() {
+            self.chapters.push(ChapterData {
+                title: DEFAULT_CHAPTER_TITLE.to_string(),
+                unlock_requirement: UnlockRequirement::Always,
+                first_level_index: 0,
+                levels_count: self.levels.len(),
+                music_file_name: None,
+            });
+        }
+        for (pack_data, pack_file_names) in packs_file_names {
+            let first_level_index = self.levels.len();
+            for pack_file_name in pack_file_names {
+                let pack_file_data = packs_files[&pack_file_name]
+                    .clone()
+                    .map_err(|error| format!("Pack '{}' not read: {}", pack_file_name, error))
+                    .and_then(|bytes| {
+                        ron::de::from_bytes::<PackFileData>(&bytes).map_err(|error| {
+                            format!("Pack '{}' not parsed: {}", pack_file_name, error)
+                        })
+                    });
+                match pack_file_data {
+                    Ok(pack_file_data) => self.levels.extend(pack_file_data.levels),
+                    Err(error) => self.errors.push(error),
+                }
+                if !self.file_names.contains(&pack_file_name) {
+                    self.file_names.push(pack_file_name);
+                }
+            }
+            let levels_count = self.levels.len() - first_level_index;
+            if 0 < levels_count {
+                self.chapters.push(ChapterData {
+                    title: pack_data.title,
+                    unlock_requirement: pack_data.unlock_requirement,
+                    first_level_index,
+                    levels_count,
+                    music_file_name: pack_data
+                        .music
+                        .map(|music| get_pack_path(file_name, &music)),
+                });
+            }
+        }
+        self.add_asset_file_names(file_name);
+        self.check_unlock_requirements();
+        self.check_max_moves();
+    }
+
+    // themes, sounds and music are reloaded with the levels when they change
+    fn add_asset_file_names(&mut self, file_name: &str) {
+        let sounds_data = self.get_sound_file_names(file_name);
+        let mut asset_file_names = self.get_theme_file_names(file_name);
+        asset_file_names.extend(
+            vec![
+                sounds_data.connection_start,
+                sounds_data.snap,
+                sounds_data.connect,
+                sounds_data.blocked,
+                sounds_data.portal_jump,
+                sounds_data.undo,
+                sounds_data.win,
+            ]
+            .into_iter()
+            .flatten(),
+        );
+        asset_file_names.extend(
+            self.chapters
+                .iter()
+                .filter_map(|chapter_data| chapter_data.music_file_name.clone()),
+        );
+        for asset_file_name in asset_file_names {
+            if !self.file_names.contains(&asset_file_name) {
+                self.file_names.push(asset_file_name);
+            }
+        }
+    }
+
+    fn check_unlock_requirements(&mut self) {
+        for chapter_data in &self.chapters {
+            if let UnlockRequirement::CompletedPack(title) = &chapter_data.unlock_requirement {
+                if !self.chapters.iter().any(|another| another.title == *title) {
+                    self.warnings.push(format!(
+                        "Pack '{}' waits for the pack '{}' that is missing or has no levels",
+                        chapter_data.title, title
+                    ));
+                }
+            }
+        }
+    }
+
+    // a level with too few moves for any path to the finish can not be completed
+    fn check_max_moves(&mut self) {
+        for level_data in &self.levels {
+            if let Some(max_moves) = level_data.max_moves {
+                let solutions_data = solver::solve(level_data, 0, MAX_CHECK_STEPS);
+                if solutions_data.is_complete && solutions_data.solutions_count == 0 {
+                    self.warnings.push(format!(
+                        "Level '{}' has no solution within {} moves",
+                        level_data.name, max_moves
+                    ));
+                }
+            }
+        }
+    }
+
+    pub fn get_theme_file_names(&self, game_data_file_name: &str) -> Vec<String> {
+        self.themes
+            .iter()
+            .map(|path| get_pack_path(game_data_file_name, path))
+            .collect()
+    }
+
+    // the same sounds with paths resolved against the main data file
+    pub fn get_sound_file_names(&self, game_data_file_name: &str) -> SoundsData {
+        let get_file_name = |path: &Option<String>| {
+            path.as_ref()
+                .map(|path| get_pack_path(game_data_file_name, path))
+        };
+        SoundsData {
+            connection_start: get_file_name(&self.sounds.connection_start),
+            snap: get_file_name(&self.sounds.snap),
+            connect: get_file_name(&self.sounds.connect),
+            blocked: get_file_name(&self.sounds.blocked),
+            portal_jump: get_file_name(&self.sounds.portal_jump),
+            undo: get_file_name(&self.sounds.undo),
+            win: get_file_name(&self.sounds.win),
+        }
+    }
+
+    pub fn get_chapter_index(&self, level_index: usize) -> Option<usize> {
+        self.chapters
+            .iter()
+            .position(|chapter_data| chapter_data.get_level_indices().contains(&level_index))
+    }
+
+    fn get_chapters_levels_count(&self) -> usize {
+        self.chapters
+            .last()
+            .map_or(0, |chapter_data| chapter_data.get_level_indices().end)
+    }
+
+    // the level of the endless mode is the only one after the chapters
+    pub fn is_generated_level(&self, level_index: usize) -> bool {
+        self.get_chapters_levels_count() <= level_index
+    }
+
+    pub fn get_generated_level(&self) -> Option<&LevelData> {
+        self.levels.get(self.get_chapters_levels_count())
+    }
+
+    // the generated level replaces the previous one, returns its index
+    pub fn set_generated_level(&mut self, level_data: LevelData) -> usize {
+        let levels_count = self.get_chapters_levels_count();
+        self.levels.truncate(levels_count);
+        self.levels.push(level_data);
+        levels_count
+    }
 }
 
-#[derive(Serialize, Deserialize)]
+// paths of packs, themes and sounds are relative to the main data file,
+// directories are expanded to their *.data files (not available at WebGL version)
+fn get_pack_path(game_data_file_name: &str, path: &str) -> String {
+    let base_directory = std::path::Path::new(game_data_file_name)
+        .parent()
+        .unwrap_or_else(|| std::path::Path::new(""));
+    base_directory.join(path).to_string_lossy().to_string()
+}
+
+fn get_pack_file_names(game_data_file_name: &str, pack_data: &PackData) -> Vec<String> {
+    let mut file_names = Vec::new();
+    for path in &pack_data.paths {
+        let path = std::path::PathBuf::from(get_pack_path(game_data_file_name, path));
+        if path.is_dir() {
+            let mut directory_file_names = Vec::new();
+            if let Ok(entries) = std::fs::read_dir(&path) {
+                for entry in entries.flatten() {
+                    let entry_path = entry.path();
+                    if entry_path
+                        .extension()
+                        .is_some_and(|e| e == PACK_FILE_EXTENSION)
+                    {
+                        directory_file_names.push(entry_path.to_string_lossy().to_string());
+                    }
+                }
+            }
+            directory_file_names.sort();
+            file_names.extend(directory_file_names);
+        } else {
+            file_names.push(path.to_string_lossy().to_string());
+        }
+    }
+    file_names
+}
+
+#[derive(Default, Clone, Serialize, Deserialize)]
 pub struct LevelData {
     pub name: String,
     pub win_count: usize,
     pub layouts: Vec<String>,
+    // in seconds, levels without a par time are not graded in the time attack
+    #[serde(default)]
+    pub par_time: Option<f32>,
+    // connections the path may have, the one to the finish included
+    #[serde(default)]
+    pub max_moves: Option<usize>,
+    // the finish takes exactly win_count connections before it instead of at least
+    #[serde(default)]
+    pub is_exact_count: bool,
+    #[serde(default)]
+    pub crossing_policy: CrossingPolicy,
+    // in layout cells, how far a new connection keeps from other connections and obstacles
+    #[serde(default)]
+    pub min_clearance: f32,
+}
+
+// what a new connection may do with the connections already made in its layout
+#[derive(Default, Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub enum CrossingPolicy {
+    // no contact at all, even a connection that ends on another one is blocked
+    #[default]
+    Strict,
+    // connections may meet at their ends but not cross
+    AllowEndpointTouching,
+    // the path may cross itself, obstacles still block
+    AllowSelfCrossing,
 }
 
 pub struct LevelAdditionalData {
     pub layouts_data: Vec<LayoutAdditionalData>,
 }
 
+impl LevelAdditionalData {
+    pub fn new(level_data: &LevelData) -> LevelAdditionalData {
+        let mut layouts_data = Vec::<LayoutAdditionalData>::with_capacity(level_data.layouts.len());
+        let mut pair_ids = Vec::new();
+        for (layout_index, layout) in level_data.layouts.iter().enumerate() {
+            let mut start_position = None;
+            let mut finish_position = None;
+            let mut points_data = Vec::new();
+            let mut obstacles_data = Vec::new();
+            let mut layout_width = 0;
+            let mut layout_height = 0;
+            for line in layout.split(['\n', ' ']) {
+                let line_width = line.len();
+                if 0 < line_width {
+                    layout_width = layout_width.max(line_width);
+                    layout_height += 1;
+                    if let Some(i) = line.chars().position(|c| c == 's') {
+                        if start_position.is_none() {
+                            start_position = Some(vec2(i as f32, (layout_height - 1) as f32));
+                        } else {
+                            // TODO: second start_position -- not good
+                        }
+                    }
+                    if let Some(i) = line.chars().position(|c| c == 'f') {
+                        if finish_position.is_none() {
+                            finish_position = Some(vec2(i as f32, (layout_height - 1) as f32));
+                        } else {
+                            // TODO: second finish_position -- not good
+                        }
+                    }
+                    for (i, char) in line.chars().enumerate() {
+                        if char.is_ascii_digit() {
+                            let point_index = points_data.len();
+                            points_data.push(PointData {
+                                position: vec2(i as f32, (layout_height - 1) as f32),
+                                point_type: PointType::Common {
+                                    layout_index: 0, // will be filled later
+                                    pair_index: 0,   // will be filled later
+                                },
+                                pair_id: Some(char),
+                            });
+                            pair_ids.push((
+                                char,
+                                PointId {
+                                    layout_index,
+                                    point_index,
+                                },
+                            ));
+                        }
+                        if char == 'z' {
+                            let radius = 0.5; // TODO: move radius to constants
+                            obstacles_data.push(ObstacleData {
+                                position: vec2(i as f32, (layout_height - 1) as f32),
+                                radius,
+                                ball: Ball::new(radius),
+                            })
+                        }
+                    }
+                }
+            }
+
+            let start_point_index = if let Some(position) = start_position {
+                points_data.push(PointData {
+                    position,
+                    point_type: PointType::Start,
+                    pair_id: None,
+                });
+                Some(points_data.len() - 1)
+            } else {
+                None
+            };
+
+            let finish_point_index = if let Some(position) = finish_position {
+                points_data.push(PointData {
+                    position,
+                    point_type: PointType::Finish,
+                    pair_id: None,
+                });
+                Some(points_data.len() - 1)
+            } else {
+                None
+            };
+
+            let mut points_grid = SpatialGrid::default();
+            for (i, point_data) in points_data.iter().enumerate() {
+                points_grid.insert(i, point_data.position, point_data.position);
+            }
+            let mut obstacles_grid = SpatialGrid::default();
+            for (i, obstacle_data) in obstacles_data.iter().enumerate() {
+                let radius = vec2(obstacle_data.radius, obstacle_data.radius);
+                obstacles_grid.insert(
+                    i,
+                    obstacle_data.position - radius,
+                    obstacle_data.position + radius,
+                );
+            }
+
+            layouts_data.push(LayoutAdditionalData {
+                size: vec2(layout_width as f32, layout_height as f32),
+                points_data,
+                obstacles_data,
+                start_point_index,
+                finish_point_index,
+                points_grid,
+                obstacles_grid,
+            });
+        }
+
+        let mut pair_ids_index = 0;
+        for layout_data in layouts_data.iter_mut() {
+            for point_data in layout_data.points_data.iter_mut() {
+                if let PointType::Common { .. } = point_data.point_type {
+                    pair_ids_index += 1;
+                    let pair_ids_index = pair_ids_index - 1;
+                    let pair_id = &pair_ids[pair_ids_index];
+                    let mut another_point_index = None;
+                    for (j, another_pair_id) in pair_ids.iter().enumerate() {
+                        if pair_ids_index != j && pair_id.0 == another_pair_id.0 {
+                            if another_point_index.is_none() {
+                                another_point_index = Some(another_pair_id);
+                            } else {
+                                // TODO: not good -- more than two points has one id
+                            }
+                        }
+                    }
+                    if let Some(another_pair_id) = another_point_index {
+                        point_data.point_type = PointType::Common {
+                            layout_index: another_pair_id.1.layout_index,
+                            pair_index: another_pair_id.1.point_index,
+                        };
+                    } else {
+                        // TODO: not good -- only one point has this id
+                    }
+                }
+            }
+        }
+
+        LevelAdditionalData { layouts_data }
+    }
+
+    pub fn get_start_layout_index(&self) -> usize {
+        let mut start_layout_index = 0;
+        for (layout_index, layout_data) in self.layouts_data.iter().enumerate() {
+            if layout_data.start_point_index.is_some() {
+                start_layout_index = layout_index;
+            }
+        }
+        start_layout_index
+    }
+
+    // the layout the player is in is fully defined by the last connection
+    pub fn get_current_layout_index(&self, connections_data: &[ConnectionData]) -> usize {
+        if let Some(connection_data) = connections_data.last() {
+            let point_data = &self.layouts_data[connection_data.layout_index].points_data
+                [connection_data.to_point_index];
+            if let PointType::Common { layout_index, .. } = point_data.point_type {
+                layout_index
+            } else {
+                connection_data.layout_index
+            }
+        } else {
+            self.get_start_layout_index()
+        }
+    }
+}
+
 pub struct LayoutAdditionalData {
     pub size: Vec2,
     pub points_data: Vec<PointData>,
     pub obstacles_data: Vec<ObstacleData>,
     pub start_point_index: Option<usize>,
     pub finish_point_index: Option<usize>,
+    pub points_grid: SpatialGrid,    // for the point queries
+    pub obstacles_grid: SpatialGrid, // for collision detection
 }
 
 pub struct PointData {
     pub position: Vec2,
     pub point_type: PointType,
+    // the digit from the layout, both points of a pair have it
+    pub pair_id: Option<char>,
 }
 
 #[derive(PartialEq)]
@@ -75,7 +672,28 @@ pub struct ConnectionData {
     pub segment: Segment, // for collision detection
 }
 
-#[derive(PartialEq)]
+impl ConnectionData {
+    pub fn new(
+        layout_data: &LayoutAdditionalData,
+        layout_index: usize,
+        from_point_index: usize,
+        to_point_index: usize,
+    ) -> ConnectionData {
+        let from_position = layout_data.points_data[from_point_index].position;
+        let to_position = layout_data.points_data[to_point_index].position;
+        ConnectionData {
+            layout_index,
+            from_point_index,
+            to_point_index,
+            segment: Segment::new(
+                Point2::new(from_position.x, from_position.y),
+                Point2::new(to_position.x, to_position.y),
+            ),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
 pub struct PointId {
     pub layout_index: usize,
     pub point_index: usize,
@@ -1,12 +1,241 @@
-pub enum GameState {
-    Start,
-    MainMenu,
-    LevelPreparing {
-        level_index: usize,
-    },
-    Level {
-        level_index: usize,
-        layout_index: usize,
-    },
+use macroquad::prelude::*;
+
+use crate::audio::Audio;
+use crate::level_state::LevelState;
+use crate::main_menu_state::MainMenuState;
+use crate::renderer::Renderer;
+use gmtk_jam_2021::game_data::{GameData, LevelAdditionalData};
+use gmtk_jam_2021::generator::{self, GeneratorData};
+use gmtk_jam_2021::progress::{ProgressData, PROGRESS_FILE_NAME};
+use gmtk_jam_2021::settings::{SettingsData, SETTINGS_FILE_NAME};
+
+// a screen of the game, only the top state of the stack gets the input,
+// all states are drawn from the bottom up so menus stay on top of the level
+pub trait State {
+    fn on_enter(&mut self, _context: &mut GameContext) {}
+
+    // egui windows are built here too, they handle the input of the frame
+    fn update(&mut self, context: &mut GameContext, egui_ctx: &egui::CtxRef) -> Transition;
+
+    fn draw(&mut self, _context: &mut GameContext) {}
+
+    fn on_exit(&mut self, _context: &mut GameContext) {}
+
+    // called before the new data replaces the old one in the context,
+    // returns false if the state can not go on with the new data
+    fn on_game_data_changed(
+        &mut self,
+        _context: &mut GameContext,
+        _new_game_data: &GameData,
+    ) -> bool {
+        true
+    }
+}
+
+pub enum Transition {
+    None,
+    Push(Box<dyn State>),
+    Pop,
+    // replaces the whole stack
+    Reset(Box<dyn State>),
     Quit,
 }
+
+// everything the states share, the rest is owned by the states
+pub struct GameContext {
+    pub game_data: GameData,
+    pub camera: Camera2D,
+    pub shown_rect: Option<Rect>, // the part of the world on the virtual screen, set while drawing
+    pub progress_data: ProgressData,
+    pub levels_additional_data: Vec<LevelAdditionalData>,
+    // the level select and the view stay as the player left them
+    pub level_select_chapter_index: usize,
+    pub level_select_page_index: usize,
+    pub is_side_by_side_view: bool,
+    // the last reload was refused because of these
+    pub data_errors: Vec<String>,
+    // problems of the data in use, it is used anyway
+    pub data_warnings: Vec<String>,
+    pub renderers: Vec<Renderer>,
+    pub renderer_index: usize,
+    pub audio: Audio,
+    pub settings_data: SettingsData,
+    pub endless_seed: Option<u64>, // the seed of the last level of the endless mode
+}
+
+impl GameContext {
+    pub fn get_renderer(&self) -> &Renderer {
+        &self.renderers[self.renderer_index]
+    }
+
+    // applies the settings that take effect every frame
+    pub fn apply_settings(&mut self) {
+        self.audio.apply_settings(&self.settings_data);
+        self.renderer_index = self
+            .settings_data
+            .theme_title
+            .as_ref()
+            .and_then(|title| {
+                self.renderers
+                    .iter()
+                    .position(|renderer| renderer.theme_data.title == *title)
+            })
+            .unwrap_or(0);
+    }
+
+    pub fn apply_changed_settings(&mut self, old_settings_data: &SettingsData) {
+        if self.settings_data == *old_settings_data {
+            return;
+        }
+        self.apply_settings();
+        if self.settings_data.is_fullscreen != old_settings_data.is_fullscreen {
+            set_fullscreen(
+                self.settings_data
+                    .is_fullscreen
+                    .unwrap_or(self.game_data.window.fullscreen),
+            );
+        }
+    }
+
+    pub fn save_progress(&self) {
+        if let Err(error) = self.progress_data.save_to_file(PROGRESS_FILE_NAME) {
+            println!("{}", error);
+        }
+    }
+
+    pub fn save_settings(&self) {
+        if let Err(error) = self.settings_data.save_to_file(SETTINGS_FILE_NAME) {
+            println!("{}", error);
+        }
+    }
+}
+
+pub fn set_fullscreen(is_fullscreen: bool) {
+    unsafe { get_internal_gl() }
+        .quad_context
+        .set_fullscreen(is_fullscreen);
+}
+
+// generates a level of the endless mode with the seed or the ones after it and opens it,
+// the error is for the player when no level fits the endless parameters
+pub fn start_endless_level(context: &mut GameContext, seed: u64) -> Result<Transition, String> {
+    let generator_data = GeneratorData {
+        seed,
+        ..context.game_data.endless.clone()
+    };
+    let generated_data = generator::generate_level(&generator_data)?;
+    let level_index = context
+        .game_data
+        .set_generated_level(generated_data.level_data);
+    context.endless_seed = Some(generated_data.seed);
+    Ok(Transition::Reset(Box::new(LevelState::new(
+        &context.game_data,
+        level_index,
+        None,
+    ))))
+}
+
+// the level with the same name in the new data, its index may have changed
+pub fn find_level_index(
+    game_data: &GameData,
+    new_game_data: &GameData,
+    level_index: usize,
+) -> Option<usize> {
+    let level_name = &game_data.levels.get(level_index)?.name;
+    new_game_data
+        .levels
+        .iter()
+        .position(|level_data| level_data.name == *level_name)
+}
+
+pub struct StateStack {
+    states: Vec<Box<dyn State>>,
+}
+
+impl StateStack {
+    pub fn new(state: Box<dyn State>, context: &mut GameContext) -> StateStack {
+        let mut state_stack = StateStack { states: Vec::new() };
+        state_stack.push(state, context);
+        state_stack
+    }
+
+    pub fn update(&mut self, context: &mut GameContext, egui_ctx: &egui::CtxRef) -> Transition {
+        match self.states.last_mut() {
+            Some(state) => state.update(context, egui_ctx),
+            None => Transition::None,
+        }
+    }
+
+    pub fn draw(&mut self, context: &mut GameContext) {
+        for state in self.states.iter_mut() {
+            state.draw(context);
+        }
+    }
+
+    // returns false when the game quits
+    pub fn apply(&mut self, transition: Transition, context: &mut GameContext) -> bool {
+        match transition {
+            Transition::None => {}
+            Transition::Push(state) => self.push(state, context),
+            Transition::Pop => {
+                self.pop(context);
+                if self.states.is_empty() {
+                    self.push(Box::new(MainMenuState::new()), context);
+                }
+            }
+            Transition::Reset(state) => {
+                self.truncate(0, context);
+                self.push(state, context);
+            }
+            Transition::Quit => {
+                self.truncate(0, context);
+                return false;
+            }
+        }
+        true
+    }
+
+    // the first state that can not go on is closed with everything on top of it,
+    // the main menu is opened if nothing is left
+    pub fn change_game_data(&mut self, context: &mut GameContext, mut new_game_data: GameData) {
+        // the generated level is not in the files
+        if let Some(level_data) = context.game_data.get_generated_level() {
+            new_game_data.set_generated_level(level_data.clone());
+        }
+        let mut states_count = self.states.len();
+        for (index, state) in self.states.iter_mut().enumerate() {
+            if !state.on_game_data_changed(context, &new_game_data) {
+                states_count = index;
+                break;
+            }
+        }
+        context.game_data = new_game_data;
+        context.levels_additional_data = context
+            .game_data
+            .levels
+            .iter()
+            .map(LevelAdditionalData::new)
+            .collect();
+        self.truncate(states_count, context);
+        if self.states.is_empty() {
+            self.push(Box::new(MainMenuState::new()), context);
+        }
+    }
+
+    fn push(&mut self, mut state: Box<dyn State>, context: &mut GameContext) {
+        state.on_enter(context);
+        self.states.push(state);
+    }
+
+    fn pop(&mut self, context: &mut GameContext) {
+        if let Some(mut state) = self.states.pop() {
+            state.on_exit(context);
+        }
+    }
+
+    fn truncate(&mut self, states_count: usize, context: &mut GameContext) {
+        while states_count < self.states.len() {
+            self.pop(context);
+        }
+    }
+}
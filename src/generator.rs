@@ -0,0 +1,187 @@
+use ron::ser::PrettyConfig;
+use serde::{Deserialize, Serialize};
+
+use crate::game_data::{LevelData, PackFileData};
+use crate::solver::{self, SolutionsData};
+
+pub const GENERATED_DIRECTORY: &str = "generated";
+// the time attack par of a generated level, per connection of its shortest solution
+pub const PAR_TIME_PER_CONNECTION: f32 = 4.0;
+// the digits of the layouts
+pub const MAX_PAIRS_COUNT: usize = 10;
+
+// the parameters of the generated levels, the endless mode takes them from game.data
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GeneratorData {
+    pub layouts_count: usize,
+    pub size: (usize, usize), // of every layout, in cells
+    pub pairs_count: usize,
+    pub obstacle_density: f32, // the share of the free cells with obstacles
+    pub win_count: usize,
+    pub seed: u64,
+    // a level is accepted with at least one and at most max_solutions solutions
+    pub max_solutions: usize,
+    pub max_attempts: usize, // levels tried, with the seeds after seed
+    pub max_search_steps: usize,
+}
+
+impl Default for GeneratorData {
+    fn default() -> Self {
+        GeneratorData {
+            layouts_count: 2,
+            size: (6, 4),
+            pairs_count: 4,
+            obstacle_density: 0.1,
+            win_count: 2,
+            seed: 0,
+            max_solutions: 3,
+            max_attempts: 200,
+            max_search_steps: 100_000,
+        }
+    }
+}
+
+impl GeneratorData {
+    pub fn check(&self) -> Result<(), String> {
+        let cells_count = self.size.0 * self.size.1 * self.layouts_count;
+        if self.layouts_count == 0 || self.size.0 == 0 || self.size.1 == 0 {
+            Err("the level needs at least one layout of one cell".to_string())
+        } else if MAX_PAIRS_COUNT < self.pairs_count {
+            Err(format!("there are only {} pairs", MAX_PAIRS_COUNT))
+        } else if self.pairs_count < self.win_count {
+            Err("win_count needs as many pairs".to_string())
+        } else if cells_count < 2 + self.pairs_count * 2 {
+            Err(format!("{} cells do not fit all the points", cells_count))
+        } else if !(0.0..1.0).contains(&self.obstacle_density) {
+            Err("obstacle_density is from 0 up to 1".to_string())
+        } else {
+            Ok(())
+        }
+    }
+}
+
+pub struct GeneratedData {
+    pub seed: u64, // the seed the level was accepted with
+    pub level_data: LevelData,
+    pub solutions_data: SolutionsData,
+}
+
+// xorshift64*, the same seed gives the same level everywhere
+struct Random {
+    state: u64,
+}
+
+impl Random {
+    fn new(seed: u64) -> Random {
+        Random {
+            state: (seed ^ 0x9e37_79b9_7f4a_7c15).max(1),
+        }
+    }
+
+    fn next(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    fn get_index(&mut self, count: usize) -> usize {
+        (self.next() % count as u64) as usize
+    }
+
+    fn get_f32(&mut self) -> f32 {
+        (self.next() >> 40) as f32 / (1u64 << 24) as f32
+    }
+}
+
+// a level with random points and obstacles, not checked by the solver
+pub fn generate_layouts(generator_data: &GeneratorData, seed: u64) -> LevelData {
+    let (width, height) = generator_data.size;
+    let layout_cells_count = width * height;
+    let mut cells = vec!['.'; layout_cells_count * generator_data.layouts_count];
+    let mut random = Random::new(seed);
+    // the points take random cells one by one, the start and the finish first
+    let mut free_cells: Vec<usize> = (0..cells.len()).collect();
+    let mut take_cell =
+        |random: &mut Random| free_cells.swap_remove(random.get_index(free_cells.len()));
+    cells[take_cell(&mut random)] = 's';
+    cells[take_cell(&mut random)] = 'f';
+    for pair_index in 0..generator_data.pairs_count {
+        let pair_id = std::char::from_digit(pair_index as u32, 10).unwrap_or('0');
+        cells[take_cell(&mut random)] = pair_id;
+        cells[take_cell(&mut random)] = pair_id;
+    }
+    for cell in cells.iter_mut().filter(|cell| **cell == '.') {
+        if random.get_f32() < generator_data.obstacle_density {
+            *cell = 'z';
+        }
+    }
+
+    let layouts = cells
+        .chunks(layout_cells_count)
+        .map(|layout_cells| {
+            let lines: Vec<String> = layout_cells
+                .chunks(width)
+                .map(|line| line.iter().collect())
+                .collect();
+            lines.join("\n")
+        })
+        .collect();
+    LevelData {
+        name: format!("Generated {}", seed),
+        win_count: generator_data.win_count,
+        layouts,
+        ..LevelData::default()
+    }
+}
+
+// tries the seeds from generator_data.seed on until the solver accepts a level
+pub fn generate_level(generator_data: &GeneratorData) -> Result<GeneratedData, String> {
+    generator_data.check()?;
+    for attempt in 0..generator_data.max_attempts {
+        let seed = generator_data.seed.wrapping_add(attempt as u64);
+        let mut level_data = generate_layouts(generator_data, seed);
+        let solutions_data = solver::solve(
+            &level_data,
+            generator_data.max_solutions,
+            generator_data.max_search_steps,
+        );
+        if solutions_data.is_bounded(generator_data.max_solutions) {
+            level_data.par_time = solutions_data
+                .shortest_solution
+                .as_ref()
+                .map(|solution| solution.len() as f32 * PAR_TIME_PER_CONNECTION);
+            return Ok(GeneratedData {
+                seed,
+                level_data,
+                solutions_data,
+            });
+        }
+    }
+    Err(format!(
+        "no level with 1 to {} solutions in {} attempts from seed {}",
+        generator_data.max_solutions, generator_data.max_attempts, generator_data.seed
+    ))
+}
+
+// a level pack file with the levels, for the manifest or a starting point of new levels
+pub fn export_levels(levels: Vec<LevelData>) -> Result<String, String> {
+    let pretty_config = PrettyConfig::new().with_separate_tuple_members(true);
+    ron::ser::to_string_pretty(&PackFileData { levels }, pretty_config)
+        .map_err(|error| format!("Level serialization failed: {}", error))
+}
+
+// saves the level as "generated/<level name>.data"
+pub fn save_to_generated_directory(level_data: &LevelData) -> Result<String, String> {
+    let text = export_levels(vec![level_data.clone()])?;
+    std::fs::create_dir_all(GENERATED_DIRECTORY).map_err(|error| error.to_string())?;
+    let file_name = format!(
+        "{}/{}.data",
+        GENERATED_DIRECTORY,
+        level_data.name.to_lowercase().replace(' ', "_")
+    );
+    std::fs::write(&file_name, text)
+        .map_err(|error| format!("Level '{}' not saved: {}", file_name, error))?;
+    Ok(file_name)
+}
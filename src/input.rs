@@ -0,0 +1,96 @@
+use macroquad::prelude::*;
+
+use gmtk_jam_2021::settings::BindingsData;
+
+// keys that can be bound in the settings, Escape is kept for menus
+pub const BINDABLE_KEYS: &[KeyCode] = &[
+    KeyCode::A,
+    KeyCode::B,
+    KeyCode::C,
+    KeyCode::D,
+    KeyCode::E,
+    KeyCode::F,
+    KeyCode::G,
+    KeyCode::H,
+    KeyCode::I,
+    KeyCode::J,
+    KeyCode::K,
+    KeyCode::L,
+    KeyCode::M,
+    KeyCode::N,
+    KeyCode::O,
+    KeyCode::P,
+    KeyCode::Q,
+    KeyCode::R,
+    KeyCode::S,
+    KeyCode::T,
+    KeyCode::U,
+    KeyCode::V,
+    KeyCode::W,
+    KeyCode::X,
+    KeyCode::Y,
+    KeyCode::Z,
+    KeyCode::Key0,
+    KeyCode::Key1,
+    KeyCode::Key2,
+    KeyCode::Key3,
+    KeyCode::Key4,
+    KeyCode::Key5,
+    KeyCode::Key6,
+    KeyCode::Key7,
+    KeyCode::Key8,
+    KeyCode::Key9,
+    KeyCode::Space,
+    KeyCode::Tab,
+    KeyCode::Backspace,
+    KeyCode::Delete,
+    KeyCode::Enter,
+    KeyCode::Left,
+    KeyCode::Right,
+    KeyCode::Up,
+    KeyCode::Down,
+    KeyCode::F1,
+    KeyCode::F2,
+    KeyCode::F3,
+    KeyCode::F4,
+    KeyCode::F5,
+    KeyCode::F6,
+    KeyCode::F7,
+    KeyCode::F8,
+    KeyCode::F9,
+    KeyCode::F10,
+    KeyCode::F11,
+    KeyCode::F12,
+];
+
+pub fn get_key_name(key_code: KeyCode) -> String {
+    format!("{:?}", key_code)
+}
+
+pub fn get_key_code(key_name: &str) -> Option<KeyCode> {
+    BINDABLE_KEYS
+        .iter()
+        .find(|key_code| get_key_name(**key_code) == key_name)
+        .copied()
+}
+
+pub fn is_binding_pressed(key_name: &str) -> bool {
+    get_key_code(key_name).is_some_and(is_key_pressed)
+}
+
+pub fn get_pressed_bindable_key() -> Option<KeyCode> {
+    BINDABLE_KEYS
+        .iter()
+        .find(|key_code| is_key_pressed(**key_code))
+        .copied()
+}
+
+// (title, key name) of every binding, in the order of the settings screen
+pub fn get_bindings(bindings_data: &mut BindingsData) -> [(&str, &mut String); 4] {
+    [
+        ("Undo last connection", &mut bindings_data.undo),
+        ("Show all layouts", &mut bindings_data.toggle_layouts_view),
+        ("Mute", &mut bindings_data.mute),
+        ("Pause", &mut bindings_data.pause),
+    ]
+}
@@ -0,0 +1,65 @@
+use macroquad::prelude::*;
+
+pub const TRANSITION_DURATION: f64 = 0.5;
+pub const HIGHLIGHT_DURATION: f64 = 1.2;
+
+// the camera slides from one layout to another when the path goes through a portal,
+// the portal is highlighted on both sides
+pub struct LayoutTransition {
+    pub from_layout_index: usize,
+    pub from_point_index: Option<usize>,
+    pub to_layout_index: usize,
+    pub to_point_index: Option<usize>,
+    pub start_time: f64,
+    // the camera jumps to the layout and the highlight does not pulse
+    pub is_reduced_motion: bool,
+}
+
+impl LayoutTransition {
+    pub fn is_moving(&self, time: f64) -> bool {
+        !self.is_reduced_motion && time - self.start_time < TRANSITION_DURATION
+    }
+
+    pub fn is_finished(&self, time: f64) -> bool {
+        HIGHLIGHT_DURATION <= time - self.start_time
+    }
+
+    pub fn get_progress(&self, time: f64) -> f32 {
+        if self.is_reduced_motion {
+            return 1.0;
+        }
+        let t = ((time - self.start_time) / TRANSITION_DURATION).clamp(0.0, 1.0) as f32;
+        t * t * (3.0 - 2.0 * t)
+    }
+
+    // camera offsets (in screen units) for the layout that leaves and the layout that comes
+    pub fn get_camera_offsets(&self, time: f64) -> (Vec2, Vec2) {
+        let direction = if self.from_layout_index < self.to_layout_index {
+            1.0
+        } else {
+            -1.0
+        };
+        let progress = self.get_progress(time);
+        (
+            vec2(-2.0 * direction * progress, 0.0),
+            vec2(2.0 * direction * (1.0 - progress), 0.0),
+        )
+    }
+
+    pub fn draw_portal_highlight(&self, position: Vec2, time: f64, color: Color) {
+        let age = (time - self.start_time) as f32;
+        let alpha = (1.0 - age / HIGHLIGHT_DURATION as f32).clamp(0.0, 1.0);
+        let radius = if self.is_reduced_motion {
+            0.4
+        } else {
+            0.35 + 0.1 * (age * 8.0).sin().abs()
+        };
+        draw_circle_lines(
+            position.x,
+            position.y,
+            radius,
+            0.06,
+            Color::new(color.r, color.g, color.b, color.a * alpha),
+        );
+    }
+}
@@ -0,0 +1,272 @@
+use egui::{Color32, Pos2, Sense, Stroke};
+use macroquad::prelude::*;
+
+use gmtk_jam_2021::game_data::{GameData, LevelAdditionalData, PointType, UnlockRequirement};
+use gmtk_jam_2021::progress::{Grade, ProgressData};
+use gmtk_jam_2021::theme::ThemeData;
+
+pub const LEVELS_PER_PAGE: usize = 6;
+pub const LEVELS_PER_ROW: usize = 3;
+pub const THUMBNAIL_SIZE: (f32, f32) = (180.0, 80.0);
+
+pub fn to_color32(color: Color) -> Color32 {
+    Color32::from_rgba_unmultiplied(
+        (color.r * 255.0) as u8,
+        (color.g * 255.0) as u8,
+        (color.b * 255.0) as u8,
+        (color.a * 255.0) as u8,
+    )
+}
+
+pub fn get_grade_color(grade: Grade) -> Color {
+    match grade {
+        Grade::Gold => GOLD,
+        Grade::Silver => LIGHTGRAY,
+        Grade::Bronze => ORANGE,
+    }
+}
+
+// all layouts of the level side by side, scaled to fit the thumbnail
+pub fn draw_level_thumbnail(
+    ui: &mut egui::Ui,
+    level_additional_data: &LevelAdditionalData,
+    theme_data: &ThemeData,
+) -> bool {
+    let (response, painter) = ui.allocate_painter(
+        egui::vec2(THUMBNAIL_SIZE.0, THUMBNAIL_SIZE.1),
+        Sense::click(),
+    );
+    let rect = response.rect;
+    painter.rect_filled(rect, 4.0, to_color32(theme_data.background_color.into()));
+
+    let gap = 1.0;
+    let layouts_data = &level_additional_data.layouts_data;
+    let total_width = layouts_data.iter().map(|layout| layout.size.x).sum::<f32>()
+        + gap * (layouts_data.len().max(1) - 1) as f32;
+    let max_height = layouts_data
+        .iter()
+        .map(|layout| layout.size.y)
+        .fold(1.0, f32::max);
+    let scale = (rect.width() / (total_width + gap))
+        .min(rect.height() / (max_height + gap))
+        .max(0.0);
+    let origin = rect.center() - egui::vec2(total_width * scale, max_height * scale) / 2.0;
+
+    let mut layout_offsets = Vec::with_capacity(layouts_data.len());
+    let mut offset_x = 0.0;
+    for layout_data in layouts_data {
+        layout_offsets.push(offset_x);
+        offset_x += layout_data.size.x + gap;
+    }
+    let to_screen = |layout_index: usize, position: Vec2| -> Pos2 {
+        origin
+            + egui::vec2(
+                (layout_offsets[layout_index] + position.x + 0.5) * scale,
+                (position.y + 0.5) * scale,
+            )
+    };
+
+    for (layout_index, layout_data) in layouts_data.iter().enumerate() {
+        let min = to_screen(layout_index, vec2(-0.5, -0.5));
+        let max = to_screen(layout_index, layout_data.size - vec2(0.5, 0.5));
+        painter.rect_stroke(
+            egui::Rect::from_min_max(min, max),
+            0.0,
+            Stroke::new(1.0, to_color32(theme_data.border_color.into())),
+        );
+    }
+
+    for (layout_index, layout_data) in layouts_data.iter().enumerate() {
+        for (point_index, point_data) in layout_data.points_data.iter().enumerate() {
+            if let PointType::Common {
+                layout_index: pair_layout_index,
+                pair_index,
+            } = point_data.point_type
+            {
+                // every pair is linked once
+                if (layout_index, point_index) < (pair_layout_index, pair_index) {
+                    let pair_position =
+                        layouts_data[pair_layout_index].points_data[pair_index].position;
+                    painter.line_segment(
+                        [
+                            to_screen(layout_index, point_data.position),
+                            to_screen(pair_layout_index, pair_position),
+                        ],
+                        Stroke::new(1.0, to_color32(theme_data.pair_link_color.into())),
+                    );
+                }
+            }
+        }
+    }
+
+    for (layout_index, layout_data) in layouts_data.iter().enumerate() {
+        for obstacle_data in &layout_data.obstacles_data {
+            painter.circle_filled(
+                to_screen(layout_index, obstacle_data.position),
+                obstacle_data.radius * scale,
+                to_color32(theme_data.obstacle_color.into()),
+            );
+        }
+        for point_data in &layout_data.points_data {
+            let color = match point_data.point_type {
+                PointType::Start => theme_data.start_color,
+                PointType::Finish => theme_data.finish_color,
+                PointType::Common { .. } => theme_data.get_pair_color(point_data.pair_id),
+            };
+            painter.circle_filled(
+                to_screen(layout_index, point_data.position),
+                0.25 * scale,
+                to_color32(color.into()),
+            );
+        }
+    }
+
+    response.clicked()
+}
+
+pub fn get_unlock_requirement_description(unlock_requirement: &UnlockRequirement) -> String {
+    match unlock_requirement {
+        UnlockRequirement::Always => "Always open".to_string(),
+        UnlockRequirement::CompletedLevels(count) => format!("Complete {} levels to unlock", count),
+        UnlockRequirement::CompletedPack(title) => format!("Complete '{}' to unlock", title),
+    }
+}
+
+// returns the index of the level the player picked
+pub fn show_level_select(
+    ui: &mut egui::Ui,
+    game_data: &GameData,
+    levels_additional_data: &[LevelAdditionalData],
+    progress_data: &ProgressData,
+    theme_data: &ThemeData,
+    chapter_index: &mut usize,
+    page_index: &mut usize,
+) -> Option<usize> {
+    if game_data.chapters.is_empty() {
+        ui.label("No levels found");
+        return None;
+    }
+    *chapter_index = (*chapter_index).min(game_data.chapters.len() - 1);
+
+    ui.horizontal_wrapped(|ui| {
+        for (index, chapter_data) in game_data.chapters.iter().enumerate() {
+            let title = if progress_data.is_chapter_unlocked(game_data, index) {
+                chapter_data.title.clone()
+            } else {
+                format!("{} (locked)", chapter_data.title)
+            };
+            if ui
+                .selectable_label(*chapter_index == index, title)
+                .clicked()
+                && *chapter_index != index
+            {
+                *chapter_index = index;
+                *page_index = 0;
+            }
+        }
+    });
+    ui.separator();
+
+    let chapter_data = &game_data.chapters[*chapter_index];
+    let level_indices = chapter_data.get_level_indices();
+    let completed_count = level_indices
+        .clone()
+        .filter(|index| progress_data.is_level_completed(&game_data.levels[*index].name))
+        .count();
+    ui.label(format!(
+        "{}. Completed: {}/{}",
+        chapter_data.title, completed_count, chapter_data.levels_count
+    ));
+    if !progress_data.is_chapter_unlocked(game_data, *chapter_index) {
+        ui.label(get_unlock_requirement_description(
+            &chapter_data.unlock_requirement,
+        ));
+        return None;
+    }
+
+    let mut selected_level_index = None;
+    let pages_count = chapter_data.levels_count.div_ceil(LEVELS_PER_PAGE).max(1);
+    *page_index = (*page_index).min(pages_count - 1);
+    let first_index = level_indices.start + *page_index * LEVELS_PER_PAGE;
+    let last_index = (first_index + LEVELS_PER_PAGE).min(level_indices.end);
+    egui::Grid::new("level_select")
+        .spacing(egui::vec2(12.0, 12.0))
+        .show(ui, |ui| {
+            for (index, level_data) in game_data
+                .levels
+                .iter()
+                .enumerate()
+                .take(last_index)
+                .skip(first_index)
+            {
+                ui.vertical(|ui| {
+                    if draw_level_thumbnail(ui, &levels_additional_data[index], theme_data) {
+                        selected_level_index = Some(index);
+                    }
+                    if ui
+                        .button(format!("{}. {}", index + 1, level_data.name))
+                        .clicked()
+                    {
+                        selected_level_index = Some(index);
+                    }
+                    ui.label(format!(
+                        "Layouts: {}  Connections: {}{}",
+                        level_data.layouts.len(),
+                        level_data.win_count,
+                        if level_data.is_exact_count {
+                            " exactly"
+                        } else {
+                            ""
+                        }
+                    ));
+                    if let Some(max_moves) = level_data.max_moves {
+                        ui.label(format!("Max moves: {}", max_moves));
+                    }
+                    match progress_data.get_level_progress(&level_data.name) {
+                        Some(level_progress) if level_progress.is_completed => {
+                            ui.colored_label(
+                                to_color32(GREEN),
+                                format!(
+                                    "Completed, best: {}",
+                                    level_progress.best_connections_count.unwrap_or_default()
+                                ),
+                            );
+                            if let Some(best_score) = level_progress.best_score {
+                                ui.label(format!("Best score: {}", best_score));
+                            }
+                            if let Some(best_time) = level_progress.best_time {
+                                let text = format!("Best time: {:.1} s", best_time);
+                                match level_progress.best_grade {
+                                    Some(grade) => ui.colored_label(
+                                        to_color32(get_grade_color(grade)),
+                                        format!("{}, {:?}", text, grade),
+                                    ),
+                                    None => ui.label(text),
+                                };
+                            }
+                        }
+                        _ => {
+                            ui.label("Not completed");
+                        }
+                    }
+                });
+                if (index - first_index + 1) % LEVELS_PER_ROW == 0 {
+                    ui.end_row();
+                }
+            }
+        });
+
+    if 1 < pages_count {
+        ui.horizontal(|ui| {
+            if ui.button("<").clicked() && 0 < *page_index {
+                *page_index -= 1;
+            }
+            ui.label(format!("Page {}/{}", *page_index + 1, pages_count));
+            if ui.button(">").clicked() && *page_index + 1 < pages_count {
+                *page_index += 1;
+            }
+        });
+    }
+
+    selected_level_index
+}
@@ -0,0 +1,692 @@
+use macroquad::prelude::*;
+
+use crate::audio::SoundEvent;
+use crate::effects::Effects;
+use crate::game_state::{find_level_index, GameContext, State, Transition};
+use crate::input;
+use crate::layout_transition::LayoutTransition;
+use crate::level_summary_state::LevelSummaryState;
+use crate::level_view;
+use crate::main_menu_state::MainMenuState;
+use crate::pause_state::{get_leave_transition, PauseState};
+use crate::toast::{self, Toast, MESSAGE_TOAST_DURATION, TOAST_DURATION};
+use gmtk_jam_2021::game_data::PointType::Common;
+use gmtk_jam_2021::game_data::{ConnectionData, CrossingPolicy, GameData, LevelAdditionalData};
+use gmtk_jam_2021::generator;
+use gmtk_jam_2021::progress::Grade;
+use gmtk_jam_2021::replay::{ReplayActionType, ReplayData};
+use gmtk_jam_2021::rules::{self, RejectionReason};
+use gmtk_jam_2021::score::ScoreData;
+use gmtk_jam_2021::simulation;
+use gmtk_jam_2021::spatial_grid::ConnectionsGrid;
+
+pub struct LevelState {
+    level_index: usize,
+    layout_index: usize,
+    level_additional_data: LevelAdditionalData,
+    connections_data: Vec<ConnectionData>,
+    connections_grid: ConnectionsGrid, // follows connections_data
+    replay_data: ReplayData,
+    undos_count: usize, // for the score
+    level_time: f64,    // the time of the replay, it stands still while the menus are open
+    layout_transition: Option<LayoutTransition>,
+    effects: Effects,
+    aim: Option<(Vec2, Vec2, Option<Vec2>)>, // start, target and intersection point
+    toast: Option<Toast>,                    // why the last connection was not made
+}
+
+impl LevelState {
+    // None starts in the layout with the start point
+    pub fn new(
+        game_data: &GameData,
+        level_index: usize,
+        layout_index: Option<usize>,
+    ) -> LevelState {
+        let level_data = &game_data.levels[level_index];
+        let level_additional_data = LevelAdditionalData::new(level_data);
+        LevelState {
+            level_index,
+            layout_index: layout_index
+                .unwrap_or_else(|| level_additional_data.get_start_layout_index()),
+            level_additional_data,
+            connections_data: Vec::new(),
+            connections_grid: ConnectionsGrid::default(),
+            replay_data: ReplayData::new(level_index, &level_data.name),
+            undos_count: 0,
+            level_time: 0.0,
+            layout_transition: None,
+            effects: Effects::default(),
+            aim: None,
+            toast: None,
+        }
+    }
+
+    fn is_side_by_side(&self, context: &GameContext) -> bool {
+        context.is_side_by_side_view && 1 < self.level_additional_data.layouts_data.len()
+    }
+
+    // the layout camera maps the mouse and the current layout to its own coordinates
+    fn update_cameras(&self, context: &mut GameContext) -> Camera2D {
+        let level_add_data = &self.level_additional_data;
+        if self.is_side_by_side(context) {
+            context.shown_rect = Some(level_view::update_screen_size(
+                &mut context.camera,
+                level_view::get_level_size(level_add_data),
+                &context.game_data,
+            ));
+            let layout_offsets = level_view::get_layout_offsets(level_add_data);
+            level_view::get_layout_camera(&context.camera, layout_offsets[self.layout_index])
+        } else {
+            context.shown_rect = Some(level_view::update_screen_size(
+                &mut context.camera,
+                level_add_data.layouts_data[self.layout_index].size,
+                &context.game_data,
+            ));
+            context.camera
+        }
+    }
+
+    // nothing is lost when the level is left now
+    fn is_leave_confirmed(&self) -> bool {
+        self.connections_data.is_empty()
+            || simulation::is_win(&self.level_additional_data, &self.connections_data)
+    }
+}
+
+impl State for LevelState {
+    fn on_enter(&mut self, context: &mut GameContext) {
+        let game_data = &context.game_data;
+        let music_file_name = game_data
+            .get_chapter_index(self.level_index)
+            .and_then(|index| game_data.chapters[index].music_file_name.as_deref());
+        context
+            .audio
+            .play_music(music_file_name, &context.settings_data);
+    }
+
+    fn update(&mut self, context: &mut GameContext, egui_ctx: &egui::CtxRef) -> Transition {
+        if is_key_pressed(KeyCode::Escape)
+            || input::is_binding_pressed(&context.settings_data.bindings.pause)
+        {
+            self.aim = None;
+            return Transition::Push(Box::new(PauseState::new(
+                self.level_index,
+                self.is_leave_confirmed(),
+            )));
+        }
+
+        self.level_time += get_frame_time() as f64;
+        self.effects.is_reduced_motion = context.settings_data.is_reduced_motion;
+        let layout_camera = self.update_cameras(context);
+        let is_side_by_side = self.is_side_by_side(context);
+        let level_add_data = &self.level_additional_data;
+        let level_data = &context.game_data.levels[self.level_index];
+        let layout_data = &level_add_data.layouts_data[self.layout_index];
+        let renderer = &context.renderers[context.renderer_index];
+
+        let current_start = simulation::get_current_start_index(
+            level_add_data,
+            &self.connections_data,
+            self.layout_index,
+        )
+        .map(|index| (index, layout_data.points_data[index].position));
+
+        let mouse_position = mouse_position();
+        let mouse_position =
+            layout_camera.screen_to_world(vec2(mouse_position.0, mouse_position.1));
+        let target_position = if let Some((current_start_index, _)) = current_start {
+            simulation::snap_target_position(
+                &self.level_additional_data,
+                &self.connections_data,
+                self.layout_index,
+                current_start_index,
+                mouse_position,
+                context.settings_data.get_snap_radius(),
+            )
+        } else {
+            mouse_position
+        };
+
+        let is_out_of_moves =
+            rules::get_moves_left(level_data, self.connections_data.len()) == Some(0);
+        // without moves left the whole aim is blocked
+        let intersection_point = if let Some((_, current_start_position)) = current_start {
+            if is_out_of_moves {
+                Some(current_start_position)
+            } else {
+                simulation::find_intersection(
+                    level_data,
+                    layout_data,
+                    &self.connections_data,
+                    &self.connections_grid,
+                    self.layout_index,
+                    current_start_position,
+                    target_position,
+                )
+            }
+        } else {
+            None
+        };
+
+        let time = get_time();
+        let highlight_color = Color::from(renderer.theme_data.highlight_color);
+        // the aim snaps to a point when it is close enough
+        let snap_position = Some(target_position)
+            .filter(|_| current_start.is_some() && target_position != mouse_position);
+        if self.effects.update_snap(
+            self.layout_index,
+            snap_position,
+            highlight_color,
+            time as f32,
+        ) {
+            context.audio.play(SoundEvent::Snap, &context.settings_data);
+        }
+        // the player waits for the camera to arrive before doing anything,
+        // all layouts are already on the screen in the side-by-side view
+        let is_input_blocked = !is_side_by_side
+            && self
+                .layout_transition
+                .as_ref()
+                .is_some_and(|transition| transition.is_moving(time));
+
+        if input::is_binding_pressed(&context.settings_data.bindings.toggle_layouts_view) {
+            context.is_side_by_side_view = !context.is_side_by_side_view;
+        }
+        if input::is_binding_pressed(&context.settings_data.bindings.mute) {
+            context.settings_data.is_muted = !context.settings_data.is_muted;
+            context.audio.apply_settings(&context.settings_data);
+            context.save_settings();
+        }
+
+        let mut transition = Transition::None;
+        let mut next_layout_index = None;
+        if let Some((current_start_index, _)) = current_start {
+            // a click on a connected point or the undo key removes connections
+            let undo_index = if is_input_blocked {
+                None
+            } else if is_mouse_button_pressed(MouseButton::Left) {
+                let index = simulation::find_undo_index(
+                    level_add_data,
+                    &self.connections_data,
+                    self.layout_index,
+                    target_position,
+                );
+                // the press starts a connection only on a point it can reach
+                let is_connection_start = index.is_none()
+                    && !egui_ctx.wants_pointer_input()
+                    && simulation::find_connection_target(
+                        level_data,
+                        level_add_data,
+                        &self.connections_data,
+                        self.layout_index,
+                        current_start_index,
+                        target_position,
+                    )
+                    .is_ok();
+                if is_connection_start {
+                    context
+                        .audio
+                        .play(SoundEvent::ConnectionStart, &context.settings_data);
+                }
+                index
+            } else if input::is_binding_pressed(&context.settings_data.bindings.undo) {
+                self.connections_data.len().checked_sub(1)
+            } else {
+                None
+            };
+            if let Some(index) = undo_index {
+                context.audio.play(SoundEvent::Undo, &context.settings_data);
+                self.connections_data.truncate(index);
+                self.connections_grid.update(&self.connections_data);
+                self.undos_count += 1;
+                self.replay_data.record(
+                    self.level_time,
+                    ReplayActionType::Undo {
+                        connection_index: index,
+                    },
+                );
+                let undo_layout_index =
+                    level_add_data.get_current_layout_index(&self.connections_data);
+                if undo_layout_index != self.layout_index {
+                    next_layout_index = Some(undo_layout_index);
+                    self.layout_transition = Some(LayoutTransition {
+                        from_layout_index: self.layout_index,
+                        from_point_index: None,
+                        to_layout_index: undo_layout_index,
+                        to_point_index: None,
+                        start_time: time,
+                        is_reduced_motion: context.settings_data.is_reduced_motion,
+                    });
+                }
+            }
+
+            if is_mouse_button_released(MouseButton::Left) && !is_input_blocked {
+                match simulation::try_connection(
+                    level_data,
+                    &self.level_additional_data,
+                    &self.connections_data,
+                    &self.connections_grid,
+                    self.layout_index,
+                    current_start_index,
+                    target_position,
+                ) {
+                    Ok(to_point_index) => {
+                        self.connections_data.push(ConnectionData::new(
+                            layout_data,
+                            self.layout_index,
+                            current_start_index,
+                            to_point_index,
+                        ));
+                        self.connections_grid.update(&self.connections_data);
+                        self.replay_data.record(
+                            self.level_time,
+                            ReplayActionType::Connect {
+                                layout_index: self.layout_index,
+                                from_point_index: current_start_index,
+                                to_point_index,
+                            },
+                        );
+                        context
+                            .audio
+                            .play(SoundEvent::Connect, &context.settings_data);
+                        let to_position = layout_data.points_data[to_point_index].position;
+                        self.effects.add_connection(
+                            self.layout_index,
+                            layout_data.points_data[current_start_index].position,
+                            to_position,
+                            highlight_color,
+                            time as f32,
+                        );
+                        if simulation::is_win(level_add_data, &self.connections_data) {
+                            // the progress keeps only the levels of the chapters
+                            let is_tracked =
+                                !context.game_data.is_generated_level(self.level_index);
+                            if is_tracked {
+                                context
+                                    .progress_data
+                                    .complete_level(&level_data.name, self.connections_data.len());
+                            }
+                            // the timer stops under the summary
+                            let time_attack_time = Some(self.level_time as f32)
+                                .filter(|_| context.settings_data.is_time_attack);
+                            let grade = time_attack_time.zip(level_data.par_time).and_then(
+                                |(level_time, par_time)| Grade::from_time(level_time, par_time),
+                            );
+                            let is_best_time = match time_attack_time {
+                                Some(level_time) if is_tracked => context
+                                    .progress_data
+                                    .record_time(&level_data.name, level_time, grade),
+                                _ => false,
+                            };
+                            let score_data = ScoreData::new(
+                                level_add_data,
+                                &self.connections_data,
+                                self.undos_count,
+                            );
+                            let is_best_score = is_tracked
+                                && context
+                                    .progress_data
+                                    .record_score(&level_data.name, score_data.get_score());
+                            if is_tracked {
+                                context.save_progress();
+                            }
+                            transition = Transition::Push(Box::new(LevelSummaryState {
+                                level_index: self.level_index,
+                                score_data,
+                                is_best_score,
+                                time: time_attack_time,
+                                grade,
+                                is_best_time,
+                                toast: None,
+                            }));
+                            let colors = renderer
+                                .theme_data
+                                .pair_colors
+                                .iter()
+                                .map(|color| Color::from(*color))
+                                .collect::<Vec<_>>();
+                            self.effects.add_win(
+                                self.layout_index,
+                                to_position,
+                                &colors,
+                                time as f32,
+                            );
+                            context.audio.play(SoundEvent::Win, &context.settings_data);
+                        }
+                        if let Common {
+                            layout_index: pair_layout_index,
+                            pair_index,
+                        } = layout_data.points_data[to_point_index].point_type
+                        {
+                            if pair_layout_index != self.layout_index {
+                                context
+                                    .audio
+                                    .play(SoundEvent::PortalJump, &context.settings_data);
+                                next_layout_index = Some(pair_layout_index);
+                                self.layout_transition = Some(LayoutTransition {
+                                    from_layout_index: self.layout_index,
+                                    from_point_index: Some(to_point_index),
+                                    to_layout_index: pair_layout_index,
+                                    to_point_index: Some(pair_index),
+                                    start_time: time,
+                                    is_reduced_motion: context.settings_data.is_reduced_motion,
+                                });
+                            }
+                        }
+                    }
+                    Err(rejection_reason) => {
+                        let is_blocked = matches!(
+                            rejection_reason,
+                            RejectionReason::BlockedByConnection
+                                | RejectionReason::BlockedByObstacle
+                                | RejectionReason::TooClose
+                                | RejectionReason::OutOfMoves
+                        );
+                        if is_blocked {
+                            context
+                                .audio
+                                .play(SoundEvent::Blocked, &context.settings_data);
+                            self.effects.add_blocked(
+                                self.layout_index,
+                                intersection_point.unwrap_or(target_position),
+                                Color::from(renderer.theme_data.blocked_aim_color),
+                                time as f32,
+                            );
+                        }
+                        self.toast = toast::get_rejection_description(rejection_reason)
+                            .map(|text| Toast::new(text, TOAST_DURATION, egui_ctx));
+                    }
+                }
+            }
+        }
+
+        self.aim = current_start
+            .filter(|_| !is_input_blocked)
+            .map(|(_, start_position)| (start_position, target_position, intersection_point));
+
+        let is_leave_confirmed = self.is_leave_confirmed();
+        let time_attack_text = if context.settings_data.is_time_attack {
+            match level_data.par_time {
+                Some(par_time) => {
+                    format!("\nTime: {:.1} s, par: {:.1} s", self.level_time, par_time)
+                }
+                None => format!("\nTime: {:.1} s", self.level_time),
+            }
+        } else {
+            String::new()
+        };
+        let mut rules_text = String::new();
+        if level_data.is_exact_count {
+            rules_text += "\nExactly this many before the finish";
+        }
+        match level_data.crossing_policy {
+            CrossingPolicy::Strict => {}
+            CrossingPolicy::AllowEndpointTouching => {
+                rules_text += "\nConnections may touch at their ends"
+            }
+            CrossingPolicy::AllowSelfCrossing => rules_text += "\nThe path may cross itself",
+        }
+        if 0.0 < level_data.min_clearance {
+            rules_text += &format!("\nKeep {} away from the rest", level_data.min_clearance);
+        }
+        if let Some(moves_left) = rules::get_moves_left(level_data, self.connections_data.len()) {
+            rules_text += &format!("\nMoves left: {}", moves_left);
+            if is_out_of_moves && current_start.is_some() {
+                rules_text += ", undo to go on";
+            }
+        }
+        let is_generated = context.game_data.is_generated_level(self.level_index);
+        let is_side_by_side_view = &mut context.is_side_by_side_view;
+        let connections_count = self.connections_data.len();
+        let level_index = self.level_index;
+        let mut export_message = None;
+        egui::Window::new("GMTK Game Jam 2021").show(egui_ctx, |ui| {
+            ui.label(format!(
+                "Playing level: '{}. {}'\nProgress:{}/{}{}{}",
+                level_index + 1,
+                level_data.name,
+                connections_count.min(level_data.win_count),
+                level_data.win_count,
+                rules_text,
+                time_attack_text,
+            ));
+            if current_start.is_none() && connections_count == 0 {
+                ui.label("There is no start in this layout.");
+                if ui.button("Go to Start").clicked() {
+                    next_layout_index = Some(level_add_data.get_start_layout_index());
+                }
+            }
+            if 1 < level_add_data.layouts_data.len() {
+                ui.checkbox(is_side_by_side_view, "Show all layouts");
+            }
+            // the generated level as a level pack file, to edit or to add to the manifest
+            if is_generated && !cfg!(target_arch = "wasm32") && ui.button("Export Level").clicked()
+            {
+                export_message = Some(match generator::save_to_generated_directory(level_data) {
+                    Ok(file_name) => format!("Level saved to {}", file_name),
+                    Err(error) => error,
+                });
+            }
+            if ui.button("Pause").clicked() {
+                transition =
+                    Transition::Push(Box::new(PauseState::new(level_index, is_leave_confirmed)));
+            }
+            if ui.button("Exit to Main Menu").clicked() {
+                transition = get_leave_transition(
+                    is_leave_confirmed,
+                    Transition::Reset(Box::new(MainMenuState::new())),
+                );
+            }
+        });
+        if let Some(text) = export_message {
+            self.toast = Some(Toast::new(text, MESSAGE_TOAST_DURATION, egui_ctx));
+        }
+        if let Some(toast) = self.toast.as_ref().filter(|toast| toast.is_shown(time)) {
+            toast.show(egui_ctx);
+        }
+
+        if let Some(next_layout_index) = next_layout_index {
+            // the aim belongs to the layout that is left
+            self.aim = None;
+            self.layout_index = next_layout_index;
+        }
+        if let Transition::Push(_) = transition {
+            self.aim = None;
+        }
+        transition
+    }
+
+    fn draw(&mut self, context: &mut GameContext) {
+        let time = get_time();
+        let is_side_by_side = self.is_side_by_side(context);
+        let mut layout_camera = self.update_cameras(context);
+        // the effects go on under the menus
+        self.effects.update(time as f32, get_frame_time());
+        let shake_offset = self.effects.get_shake_offset(time as f32);
+        context.camera.offset = shake_offset;
+        layout_camera.offset = shake_offset;
+        set_camera(&layout_camera);
+
+        let camera = context.camera;
+        let renderer = &context.renderers[context.renderer_index];
+        let level_add_data = &self.level_additional_data;
+        let layout_index = self.layout_index;
+        let layout_data = &level_add_data.layouts_data[layout_index];
+        let connections_data = &self.connections_data;
+        let effects = &self.effects;
+        let effects_thickness = renderer.theme_data.line_thickness;
+        let highlight_color = Color::from(renderer.theme_data.highlight_color);
+
+        match &self.layout_transition {
+            _ if is_side_by_side => {
+                let layout_offsets = level_view::get_layout_offsets(level_add_data);
+                level_view::draw_pair_arcs(
+                    level_add_data,
+                    &layout_offsets,
+                    Color::from(renderer.theme_data.pair_link_color),
+                    renderer.theme_data.line_thickness / 2.0,
+                );
+                for (index, other_layout_data) in level_add_data.layouts_data.iter().enumerate() {
+                    set_camera(&level_view::get_layout_camera(
+                        &camera,
+                        layout_offsets[index],
+                    ));
+                    renderer.draw_layout(other_layout_data, connections_data, index);
+                    effects.draw(index, time as f32, effects_thickness);
+                }
+                set_camera(&layout_camera);
+                renderer.draw_current_layout_frame(layout_data);
+                if let Some(transition) = &self.layout_transition {
+                    if let Some(point_index) = transition.from_point_index {
+                        set_camera(&level_view::get_layout_camera(
+                            &camera,
+                            layout_offsets[transition.from_layout_index],
+                        ));
+                        transition.draw_portal_highlight(
+                            level_add_data.layouts_data[transition.from_layout_index].points_data
+                                [point_index]
+                                .position,
+                            time,
+                            highlight_color,
+                        );
+                        set_camera(&layout_camera);
+                    }
+                    if let Some(point_index) = transition.to_point_index {
+                        transition.draw_portal_highlight(
+                            layout_data.points_data[point_index].position,
+                            time,
+                            highlight_color,
+                        );
+                    }
+                }
+            }
+            Some(transition)
+                if transition.is_moving(time) && transition.to_layout_index == layout_index =>
+            {
+                let (from_offset, to_offset) = transition.get_camera_offsets(time);
+                let from_layout_data = &level_add_data.layouts_data[transition.from_layout_index];
+                let mut transition_camera = camera;
+                level_view::update_screen_size(
+                    &mut transition_camera,
+                    from_layout_data.size,
+                    &context.game_data,
+                );
+                transition_camera.offset = from_offset;
+                set_camera(&transition_camera);
+                renderer.draw_layout(
+                    from_layout_data,
+                    connections_data,
+                    transition.from_layout_index,
+                );
+                if let Some(point_index) = transition.from_point_index {
+                    transition.draw_portal_highlight(
+                        from_layout_data.points_data[point_index].position,
+                        time,
+                        highlight_color,
+                    );
+                }
+
+                let mut transition_camera = camera;
+                transition_camera.offset = to_offset;
+                set_camera(&transition_camera);
+                renderer.draw_layout(layout_data, connections_data, layout_index);
+                effects.draw(layout_index, time as f32, effects_thickness);
+                if let Some(point_index) = transition.to_point_index {
+                    transition.draw_portal_highlight(
+                        layout_data.points_data[point_index].position,
+                        time,
+                        highlight_color,
+                    );
+                }
+                set_camera(&camera);
+            }
+            _ => {
+                renderer.draw_layout(layout_data, connections_data, layout_index);
+                effects.draw(layout_index, time as f32, effects_thickness);
+                if let Some(transition) = &self.layout_transition {
+                    if let Some(point_index) = transition.to_point_index {
+                        if transition.to_layout_index == layout_index {
+                            transition.draw_portal_highlight(
+                                layout_data.points_data[point_index].position,
+                                time,
+                                highlight_color,
+                            );
+                        }
+                    }
+                }
+            }
+        }
+
+        if let Some((start_position, target_position, intersection_point)) = self.aim {
+            renderer.draw_aim(start_position, target_position, intersection_point);
+        }
+        if self
+            .layout_transition
+            .as_ref()
+            .is_some_and(|transition| transition.is_finished(time))
+        {
+            self.layout_transition = None;
+        }
+    }
+
+    fn on_exit(&mut self, context: &mut GameContext) {
+        // the generated level will not be there to watch the replay
+        if !context.game_data.is_generated_level(self.level_index) {
+            save_replay(&self.replay_data);
+        }
+    }
+
+    // the connections that still fit the edited level are kept
+    fn on_game_data_changed(
+        &mut self,
+        context: &mut GameContext,
+        new_game_data: &GameData,
+    ) -> bool {
+        let new_level_index =
+            match find_level_index(&context.game_data, new_game_data, self.level_index) {
+                Some(new_level_index) => new_level_index,
+                // the replay is saved when the level is closed
+                None => return false,
+            };
+        if !context.game_data.is_generated_level(self.level_index) {
+            save_replay(&self.replay_data);
+        }
+        let level_data = &new_game_data.levels[new_level_index];
+        self.level_additional_data = LevelAdditionalData::new(level_data);
+        self.layout_transition = None;
+        self.effects.clear();
+        self.aim = None;
+        self.connections_data = simulation::restore_connections(
+            level_data,
+            &self.level_additional_data,
+            &self.connections_data,
+        );
+        self.connections_grid.update(&self.connections_data);
+        self.replay_data = ReplayData::new(new_level_index, &level_data.name);
+        for connection_data in &self.connections_data {
+            self.replay_data.record(
+                self.level_time,
+                ReplayActionType::Connect {
+                    layout_index: connection_data.layout_index,
+                    from_point_index: connection_data.from_point_index,
+                    to_point_index: connection_data.to_point_index,
+                },
+            );
+        }
+        self.level_index = new_level_index;
+        self.layout_index = self
+            .level_additional_data
+            .get_current_layout_index(&self.connections_data);
+        true
+    }
+}
+
+pub fn save_replay(replay_data: &ReplayData) {
+    if replay_data.actions.is_empty() || cfg!(target_arch = "wasm32") {
+        return;
+    }
+    match replay_data.save_to_replays_directory() {
+        Ok(file_name) => println!("replay saved to {}", file_name),
+        Err(error) => println!("{}", error),
+    }
+}
@@ -0,0 +1,154 @@
+use macroquad::prelude::*;
+
+use crate::game_state::{find_level_index, start_endless_level, GameContext, State, Transition};
+use crate::level_select::{get_grade_color, to_color32};
+use crate::level_state::LevelState;
+use crate::main_menu_state::MainMenuState;
+use crate::toast::{Toast, MESSAGE_TOAST_DURATION};
+use gmtk_jam_2021::game_data::GameData;
+use gmtk_jam_2021::progress::Grade;
+use gmtk_jam_2021::score::ScoreData;
+
+// the results of a finished level, on top of the level
+pub struct LevelSummaryState {
+    pub level_index: usize,
+    pub score_data: ScoreData,
+    pub is_best_score: bool,
+    pub time: Option<f32>, // None if the level was not played in the time attack
+    pub grade: Option<Grade>,
+    pub is_best_time: bool,
+    pub toast: Option<Toast>, // why the next level of the endless mode did not start
+}
+
+impl State for LevelSummaryState {
+    fn update(&mut self, context: &mut GameContext, egui_ctx: &egui::CtxRef) -> Transition {
+        let mut transition = Transition::None;
+        let mut is_next_generated = false;
+        let game_data = &context.game_data;
+        let level_data = &game_data.levels[self.level_index];
+        let level_progress = context.progress_data.get_level_progress(&level_data.name);
+        egui::Window::new("Level Complete!").show(egui_ctx, |ui| {
+            ui.label(format!("'{}. {}'", self.level_index + 1, level_data.name));
+            let score_data = &self.score_data;
+            let penalties = score_data.get_penalties();
+            egui::Grid::new("score").show(ui, |ui| {
+                let rows = [
+                    ("Length", format!("{:.1}", score_data.length)),
+                    ("Connections", score_data.connections_count.to_string()),
+                    ("Portal jumps", score_data.portal_jumps_count.to_string()),
+                    ("Undos", score_data.undos_count.to_string()),
+                ];
+                for ((title, value), penalty) in rows.iter().zip(penalties.iter()) {
+                    ui.label(*title);
+                    ui.label(value);
+                    ui.label(format!("-{:.0}", penalty));
+                    ui.end_row();
+                }
+            });
+            ui.heading(format!("Score: {}", score_data.get_score()));
+            if self.is_best_score {
+                ui.label("New best score!");
+            } else if let Some(best_score) =
+                level_progress.and_then(|level_progress| level_progress.best_score)
+            {
+                ui.label(format!("Best score: {}", best_score));
+            }
+            if let Some(time) = self.time {
+                ui.separator();
+                match level_data.par_time {
+                    Some(par_time) => {
+                        ui.label(format!("Time: {:.1} s, par: {:.1} s", time, par_time))
+                    }
+                    None => ui.label(format!("Time: {:.1} s", time)),
+                };
+                match (self.grade, level_data.par_time) {
+                    (Some(grade), _) => {
+                        ui.colored_label(
+                            to_color32(get_grade_color(grade)),
+                            format!("Grade: {:?}", grade),
+                        );
+                    }
+                    (None, Some(_)) => {
+                        ui.label("Too slow for a grade");
+                    }
+                    (None, None) => {
+                        ui.label("This level has no par time");
+                    }
+                }
+                if self.is_best_time {
+                    ui.label("New best time!");
+                } else if let Some(best_time) =
+                    level_progress.and_then(|level_progress| level_progress.best_time)
+                {
+                    ui.label(format!("Best time: {:.1} s", best_time));
+                }
+            }
+
+            ui.separator();
+            let next_level_index = self.level_index + 1;
+            if game_data.is_generated_level(self.level_index) {
+                if ui.button("Next Level").clicked() {
+                    is_next_generated = true;
+                }
+            } else if next_level_index < game_data.levels.len()
+                && !game_data.is_generated_level(next_level_index)
+            {
+                if context
+                    .progress_data
+                    .is_level_unlocked(game_data, next_level_index)
+                {
+                    if ui.button("Next Level").clicked() {
+                        transition = Transition::Reset(Box::new(LevelState::new(
+                            game_data,
+                            next_level_index,
+                            None,
+                        )));
+                    }
+                } else {
+                    ui.label("The next chapter is still locked.");
+                }
+            } else {
+                ui.label("Thank you for playing!");
+            }
+            if ui.button("Restart Level").clicked() {
+                transition =
+                    Transition::Reset(Box::new(LevelState::new(game_data, self.level_index, None)));
+            }
+            if ui.button("Exit to Main Menu").clicked() {
+                transition = Transition::Reset(Box::new(MainMenuState::new()));
+            }
+        });
+        // the endless mode goes on with the seed after the one of the level
+        if is_next_generated {
+            let seed = context.endless_seed.map_or(0, |seed| seed.wrapping_add(1));
+            match start_endless_level(context, seed) {
+                Ok(endless_transition) => transition = endless_transition,
+                Err(error) => {
+                    self.toast = Some(Toast::new(error, MESSAGE_TOAST_DURATION, egui_ctx))
+                }
+            }
+        }
+        if let Some(toast) = self
+            .toast
+            .as_ref()
+            .filter(|toast| toast.is_shown(get_time()))
+        {
+            toast.show(egui_ctx);
+        }
+        transition
+    }
+
+    fn on_game_data_changed(
+        &mut self,
+        context: &mut GameContext,
+        new_game_data: &GameData,
+    ) -> bool {
+        match find_level_index(&context.game_data, new_game_data, self.level_index) {
+            Some(new_level_index) => {
+                self.level_index = new_level_index;
+                true
+            }
+            None => false,
+        }
+    }
+}
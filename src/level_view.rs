@@ -0,0 +1,145 @@
+use macroquad::prelude::*;
+
+use gmtk_jam_2021::game_data::{GameData, LevelAdditionalData, PointType, ScalingPolicy};
+
+// arrangement of all layouts of a level in one world for the side-by-side view
+
+pub const LAYOUTS_GAP: f32 = 1.0;
+pub const MAX_LAYOUTS_IN_ROW: usize = 3;
+pub const PAIR_ARC_SEGMENTS: usize = 24;
+
+fn get_columns_count(layouts_count: usize) -> usize {
+    if layouts_count <= MAX_LAYOUTS_IN_ROW {
+        layouts_count.max(1)
+    } else {
+        (layouts_count as f32).sqrt().ceil() as usize
+    }
+}
+
+fn get_cell_size(level_additional_data: &LevelAdditionalData) -> Vec2 {
+    level_additional_data
+        .layouts_data
+        .iter()
+        .fold(Vec2::ZERO, |size, layout_data| size.max(layout_data.size))
+}
+
+// position of every layout's (0, 0) point in the world
+pub fn get_layout_offsets(level_additional_data: &LevelAdditionalData) -> Vec<Vec2> {
+    let layouts_count = level_additional_data.layouts_data.len();
+    let columns_count = get_columns_count(layouts_count);
+    let cell_size = get_cell_size(level_additional_data) + Vec2::splat(LAYOUTS_GAP);
+    (0..layouts_count)
+        .map(|index| {
+            vec2(
+                (index % columns_count) as f32 * cell_size.x,
+                (index / columns_count) as f32 * cell_size.y,
+            )
+        })
+        .collect()
+}
+
+pub fn get_level_size(level_additional_data: &LevelAdditionalData) -> Vec2 {
+    let layouts_count = level_additional_data.layouts_data.len();
+    let columns_count = get_columns_count(layouts_count);
+    let rows_count = layouts_count.div_ceil(columns_count).max(1);
+    let cell_size = get_cell_size(level_additional_data);
+    vec2(
+        columns_count as f32 * cell_size.x + (columns_count - 1) as f32 * LAYOUTS_GAP,
+        rows_count as f32 * cell_size.y + (rows_count - 1) as f32 * LAYOUTS_GAP,
+    )
+}
+
+// the camera that draws a layout at its place in the world using the layout's own coordinates
+pub fn get_layout_camera(camera: &Camera2D, layout_offset: Vec2) -> Camera2D {
+    let mut layout_camera = *camera;
+    layout_camera.target -= layout_offset;
+    layout_camera
+}
+
+// links the two points of every pair that live in different layouts
+pub fn draw_pair_arcs(
+    level_additional_data: &LevelAdditionalData,
+    layout_offsets: &[Vec2],
+    color: Color,
+    thickness: f32,
+) {
+    for (layout_index, layout_data) in level_additional_data.layouts_data.iter().enumerate() {
+        for point_data in &layout_data.points_data {
+            if let PointType::Common {
+                layout_index: pair_layout_index,
+                pair_index,
+            } = point_data.point_type
+            {
+                // every pair is linked once, pairs inside one layout need no arc
+                if layout_index < pair_layout_index {
+                    let from = point_data.position + layout_offsets[layout_index];
+                    let to = level_additional_data.layouts_data[pair_layout_index].points_data
+                        [pair_index]
+                        .position
+                        + layout_offsets[pair_layout_index];
+                    let middle = (from + to) / 2.0;
+                    let normal = (to - from).perp().normalize_or_zero();
+                    let control = middle - normal * (to - from).length() * 0.2;
+                    let mut previous = from;
+                    for i in 1..=PAIR_ARC_SEGMENTS {
+                        let t = i as f32 / PAIR_ARC_SEGMENTS as f32;
+                        let point = from * (1.0 - t) * (1.0 - t)
+                            + control * 2.0 * t * (1.0 - t)
+                            + to * t * t;
+                        draw_line(previous.x, previous.y, point.x, point.y, thickness, color);
+                        previous = point;
+                    }
+                }
+            }
+        }
+    }
+}
+
+// returns the part of the world shown on the virtual screen
+pub fn update_screen_size(camera: &mut Camera2D, virtual_size: Vec2, game_data: &GameData) -> Rect {
+    // TODO: add delay and test for new screen size
+
+    let real_screen_size = vec2(screen_width(), screen_height());
+    let real_aspect_ratio = real_screen_size.x / real_screen_size.y;
+    let resolution = vec2(game_data.resolution.0, game_data.resolution.1);
+    let target_aspect_ratio = if 0.0 < resolution.x && 0.0 < resolution.y {
+        resolution.x / resolution.y
+    } else {
+        real_aspect_ratio
+    };
+    let content_size = virtual_size + Vec2::splat(2.0 * game_data.screen.min_margin);
+
+    let fit = |size: Vec2, aspect_ratio: f32| {
+        if size.x / size.y < aspect_ratio {
+            vec2(aspect_ratio * size.y, size.y)
+        } else {
+            vec2(size.x, size.x / aspect_ratio)
+        }
+    };
+    let (virtual_screen_size, shown_size) = match game_data.screen.scaling_policy {
+        ScalingPolicy::Expand => {
+            let size = fit(content_size, real_aspect_ratio);
+            (size, size)
+        }
+        ScalingPolicy::Letterbox => {
+            let size = fit(content_size, target_aspect_ratio);
+            (fit(size, real_aspect_ratio), size)
+        }
+        ScalingPolicy::Stretch => {
+            let size = fit(content_size, target_aspect_ratio);
+            (size, size)
+        }
+    };
+
+    camera.zoom = vec2(2.0 / virtual_screen_size.x, -2.0 / virtual_screen_size.y);
+    camera.target = (vec2(virtual_size.x, virtual_size.y) - Vec2::ONE) / 2.0;
+    camera.offset = Vec2::ZERO;
+    set_camera(camera);
+
+    Rect::new(
+        camera.target.x - shown_size.x / 2.0,
+        camera.target.y - shown_size.y / 2.0,
+        shown_size.x,
+        shown_size.y,
+    )
+}
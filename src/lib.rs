@@ -0,0 +1,13 @@
+pub mod data_watcher;
+pub mod game_data;
+pub mod generator;
+pub mod progress;
+pub mod replay;
+pub mod rules;
+pub mod score;
+pub mod settings;
+pub mod simulation;
+pub mod solver;
+pub mod spatial_grid;
+pub mod storage;
+pub mod theme;
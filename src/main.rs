@@ -1,625 +1,251 @@
 use macroquad::prelude::*;
-use parry2d::math::Isometry;
-use parry2d::na::{Point2, Vector2};
-use parry2d::query::{Ray, RayCast};
-use parry2d::shape::{Ball, Segment};
 
-use game_data::GameData;
-use game_state::GameState;
-
-use crate::game_data::PointType::{Common, Finish, Start};
-use crate::game_data::{
-    ConnectionData, LayoutAdditionalData, LevelAdditionalData, ObstacleData, PointData, PointId,
-    PointType,
-};
-
-mod game_data;
+use audio::Audio;
+use command_line::{CommandLineArgs, DEFAULT_DATA_FILE_NAME, USAGE};
+use game_state::{GameContext, State, StateStack, Transition};
+use gmtk_jam_2021::data_watcher::DataWatcher;
+use gmtk_jam_2021::game_data::{GameData, LevelAdditionalData, ScalingPolicy};
+use gmtk_jam_2021::progress::{ProgressData, PROGRESS_FILE_NAME};
+use gmtk_jam_2021::settings::{SettingsData, SETTINGS_FILE_NAME};
+use gmtk_jam_2021::theme::ThemeData;
+use level_state::LevelState;
+use main_menu_state::MainMenuState;
+use renderer::Renderer;
+
+mod audio;
+mod command_line;
+mod effects;
 mod game_state;
+mod input;
+mod layout_transition;
+mod level_select;
+mod level_state;
+mod level_summary_state;
+mod level_view;
+mod main_menu_state;
+mod pause_state;
+mod renderer;
+mod replay_state;
+mod settings_menu;
+mod settings_state;
+mod toast;
+
+fn window_conf() -> Conf {
+    // the window is created before main() runs, so the data file is read here once more
+    let data_file_name = CommandLineArgs::parse(std::env::args())
+        .map(|command_line_args| command_line_args.data_file_name)
+        .unwrap_or_else(|_| DEFAULT_DATA_FILE_NAME.to_string());
+    let window_data = std::fs::read(data_file_name)
+        .ok()
+        .and_then(|bytes| GameData::from_bytes(&bytes).ok())
+        .map(|game_data| game_data.window)
+        .unwrap_or_default();
+    let mut conf = window_data.to_conf();
+    if let Some(is_fullscreen) = SettingsData::load_from_file(SETTINGS_FILE_NAME).is_fullscreen {
+        conf.fullscreen = is_fullscreen;
+    }
+    conf
+}
 
-#[macroquad::main("GMTK Game Jam 2021")]
+#[macroquad::main(window_conf)]
 async fn main() {
-    let file_name = "assets/game.data";
-    let game_data = GameData::load_from_file(file_name)
-        .await
-        .expect("Game data not read");
+    let command_line_args = match CommandLineArgs::parse(std::env::args()) {
+        Ok(command_line_args) => command_line_args,
+        Err(message) => {
+            println!("{}", message);
+            std::process::exit(2);
+        }
+    };
+    if command_line_args.is_help {
+        println!("{}", USAGE);
+        std::process::exit(0);
+    }
+    let file_name = command_line_args.data_file_name.as_str();
+    let game_data = match GameData::load_from_file(file_name).await {
+        Ok(game_data) => game_data,
+        Err(error) => {
+            println!("{}", error);
+            std::process::exit(2);
+        }
+    };
 
-    let mut game_state = GameState::Start;
+    let start_level = match command_line_args.get_start_level(&game_data) {
+        Ok(start_level) => start_level,
+        Err(message) => {
+            println!("{}", message);
+            std::process::exit(2);
+        }
+    };
 
-    let mut camera = Default::default();
+    let camera = Default::default();
     set_camera(&camera);
 
-    // TODO: move under GameState::Level
-    let mut connections_data = Vec::<ConnectionData>::new();
-    let mut level_additional_data = LevelAdditionalData {
-        layouts_data: vec![],
+    let mut data_watcher = DataWatcher::new(&game_data.file_names, get_time());
+    // the game starts even with broken packs, only reloads are refused
+    let mut data_warnings = game_data.errors.clone();
+    data_warnings.extend(game_data.warnings.iter().cloned());
+    let renderers = load_renderers(&game_data, file_name, &mut data_warnings).await;
+    let audio = Audio::load(&game_data, file_name, &mut data_warnings).await;
+    let mut context = GameContext {
+        camera,
+        shown_rect: None,
+        progress_data: ProgressData::load_from_file(PROGRESS_FILE_NAME),
+        levels_additional_data: game_data
+            .levels
+            .iter()
+            .map(LevelAdditionalData::new)
+            .collect(),
+        level_select_chapter_index: 0,
+        level_select_page_index: 0,
+        is_side_by_side_view: false,
+        data_errors: Vec::new(),
+        data_warnings,
+        renderers,
+        renderer_index: 0,
+        audio,
+        settings_data: SettingsData::load_from_file(SETTINGS_FILE_NAME),
+        endless_seed: None,
+        game_data,
     };
-    //
-
-    'game_loop: loop {
-        clear_background(BLACK);
-
-        match &game_state {
-            GameState::Start => {
-                game_state = GameState::MainMenu {};
-                egui_macroquad::ui(|_| {});
-            }
-
-            GameState::MainMenu {} => {
-                egui_macroquad::ui(|egui_ctx| {
-                    egui::Window::new("GMTK Game Jam 2021").show(egui_ctx, |ui| {
-                        ui.label("Select level:");
-                        for (index, level_data) in game_data.levels.iter().enumerate() {
-                            if ui
-                                .button(format!("{}. {}", index + 1, level_data.name))
-                                .clicked()
-                            {
-                                game_state = GameState::LevelPreparing { level_index: index };
-                            }
-                        }
-                    });
-                });
-            }
-
-            GameState::LevelPreparing { level_index } => {
-                egui_macroquad::ui(|_| {});
-
-                let level_data = &(game_data.levels[*level_index]);
-                let mut start_layout_index = 0;
-                let mut layouts_data =
-                    Vec::<LayoutAdditionalData>::with_capacity(level_data.layouts.len());
-                let mut pair_ids = Vec::new();
-                for (layout_index, layout) in level_data.layouts.iter().enumerate() {
-                    let mut start_position = None;
-                    let mut finish_position = None;
-                    let mut points_data = Vec::new();
-                    let mut obstacles_data = Vec::new();
-                    let mut layout_width = 0;
-                    let mut layout_height = 0;
-                    for line in layout.split(|c| c == '\n' || c == ' ') {
-                        let line_width = line.len();
-                        if 0 < line_width {
-                            layout_width = layout_width.max(line_width);
-                            layout_height += 1;
-                            match line.chars().position(|c| c == 's') {
-                                Some(i) => {
-                                    if start_position == None {
-                                        start_position =
-                                            Some(vec2(i as f32, (layout_height - 1) as f32));
-                                    } else {
-                                        // TODO: second start_position -- not good
-                                    }
-                                }
-                                _ => {}
-                            }
-                            match line.chars().position(|c| c == 'f') {
-                                Some(i) => {
-                                    if finish_position == None {
-                                        finish_position =
-                                            Some(vec2(i as f32, (layout_height - 1) as f32));
-                                    } else {
-                                        // TODO: second finish_position -- not good
-                                    }
-                                }
-                                _ => {}
-                            }
-                            for (i, char) in line.chars().enumerate() {
-                                if char.is_digit(10) {
-                                    let point_index = points_data.len();
-                                    points_data.push(PointData {
-                                        position: vec2(i as f32, (layout_height - 1) as f32),
-                                        point_type: Common {
-                                            layout_index: 0, // will be filled later
-                                            pair_index: 0,   // will be filled later
-                                        },
-                                    });
-                                    pair_ids.push((
-                                        char,
-                                        PointId {
-                                            layout_index,
-                                            point_index,
-                                        },
-                                    ));
-                                }
-                                if char == 'z' {
-                                    let radius = 0.5; // TODO: move radius to constants
-                                    obstacles_data.push(ObstacleData {
-                                        position: vec2(i as f32, (layout_height - 1) as f32),
-                                        radius,
-                                        ball: Ball::new(radius),
-                                    })
-                                }
-                            }
-                        }
-                    }
-
-                    let start_point_index = if let Some(position) = start_position {
-                        points_data.push(PointData {
-                            position,
-                            point_type: Start,
-                        });
-                        Some(points_data.len() - 1)
-                    } else {
-                        None
-                    };
-
-                    let finish_point_index = if let Some(position) = finish_position {
-                        points_data.push(PointData {
-                            position,
-                            point_type: Finish,
-                        });
-                        Some(points_data.len() - 1)
-                    } else {
-                        None
-                    };
-
-                    layouts_data.push(LayoutAdditionalData {
-                        size: vec2(layout_width as f32, layout_height as f32),
-                        points_data,
-                        obstacles_data,
-                        start_point_index,
-                        finish_point_index,
-                    });
-
-                    if start_point_index != None {
-                        start_layout_index = layout_index;
-                    }
-                }
-
-                let mut pair_ids_index = 0;
-                for (layout_index, _) in level_data.layouts.iter().enumerate() {
-                    let layout_data = &mut layouts_data[layout_index];
-                    for (_, point_data) in layout_data.points_data.iter_mut().enumerate() {
-                        match point_data.point_type {
-                            PointType::Common { .. } => {
-                                pair_ids_index += 1;
-                                let pair_ids_index = pair_ids_index - 1;
-                                let pair_id = &pair_ids[pair_ids_index];
-                                let mut another_point_index = None;
-                                for (j, another_pair_id) in pair_ids.iter().enumerate() {
-                                    if pair_ids_index != j && pair_id.0 == another_pair_id.0 {
-                                        if another_point_index == None {
-                                            another_point_index = Some(another_pair_id);
-                                        } else {
-                                            // TODO: not good -- more than two points has one id
-                                        }
-                                    }
-                                }
-                                if let Some(another_pair_id) = another_point_index {
-                                    point_data.point_type = Common {
-                                        layout_index: another_pair_id.1.layout_index,
-                                        pair_index: another_pair_id.1.point_index,
-                                    };
-                                } else {
-                                    // TODO: not good -- only one point has this id
-                                }
-                            }
-                            _ => {}
-                        }
-                    }
-                }
-
-                connections_data.clear();
-                level_additional_data = LevelAdditionalData { layouts_data };
-                game_state = GameState::Level {
-                    level_index: *level_index,
-                    layout_index: start_layout_index,
-                };
-            }
-
-            GameState::Level {
-                level_index,
-                layout_index,
-            } => {
-                let mut next_game_state = None;
-                let level_add_data = &level_additional_data;
-                let level_data = &(game_data.levels[*level_index]);
-                let layout_data = &level_add_data.layouts_data[*layout_index];
-                update_screen_size(&mut camera, layout_data.size);
-
-                draw_rectangle_lines(
-                    -0.5,
-                    -0.5,
-                    layout_data.size.x,
-                    layout_data.size.y,
-                    0.1,
-                    GRAY,
-                );
-                for y in 0..layout_data.size.y as i32 {
-                    for x in 0..layout_data.size.x as i32 {
-                        draw_circle(x as f32, y as f32, 0.1, DARKGRAY);
-                    }
-                }
-
-                let point_radius = 0.25;
-                for point_data in &layout_data.points_data {
-                    draw_circle(
-                        point_data.position.x,
-                        point_data.position.y,
-                        point_radius,
-                        ORANGE,
-                    );
-                }
-                for obstacle_data in &layout_data.obstacles_data {
-                    draw_circle(
-                        obstacle_data.position.x,
-                        obstacle_data.position.y,
-                        obstacle_data.radius,
-                        BLUE,
-                    );
-                }
-
-                if let Some(start_point_index) = layout_data.start_point_index {
-                    draw_circle(
-                        layout_data.points_data[start_point_index].position.x,
-                        layout_data.points_data[start_point_index].position.y,
-                        point_radius,
-                        YELLOW,
-                    );
-                }
-
-                if let Some(finish_point_index) = layout_data.finish_point_index {
-                    draw_circle(
-                        layout_data.points_data[finish_point_index].position.x,
-                        layout_data.points_data[finish_point_index].position.y,
-                        point_radius,
-                        GREEN,
-                    );
-                }
-
-                let current_start = if let Some(connection_data) = connections_data.last() {
-                    let point_data = &level_add_data.layouts_data[connection_data.layout_index]
-                        .points_data[connection_data.to_point_index];
-                    if let Common {
-                        layout_index: _,
-                        pair_index,
-                    } = point_data.point_type
-                    {
-                        Some((pair_index, layout_data.points_data[pair_index].position))
-                    } else {
-                        None
-                    }
-                } else if let Some(start_point_index) = layout_data.start_point_index {
-                    Some((
-                        start_point_index,
-                        layout_data.points_data[start_point_index].position,
-                    ))
-                } else {
-                    panic!("Can not find start!");
-                };
-
-                let mouse_position = mouse_position();
-                let mouse_position =
-                    camera.screen_to_world(vec2(mouse_position.0, mouse_position.1));
-                let target_position =
-                    if let Some((current_start_index, current_start_position)) = current_start {
-                        let mut target_position = mouse_position;
-                        for (i, point_data) in layout_data.points_data.iter().enumerate() {
-                            if i != current_start_index
-                                && mouse_position.distance_squared(point_data.position)
-                                    < point_radius * point_radius
-                            {
-                                if !connections_data.iter().any(|elem| {
-                                    elem.layout_index == *layout_index && elem.from_point_index == i
-                                        || elem.to_point_index == i
-                                }) {
-                                    target_position = point_data.position;
-                                }
-                            }
-                        }
-                        target_position
-                    } else {
-                        mouse_position
-                    };
-
-                let intersection_point = {
-                    let mut intersection_point = None;
-                    if let Some((_, current_start_position)) = current_start {
-                        let vector = target_position - current_start_position;
-                        let ray = Ray::new(
-                            Point2::new(current_start_position.x, current_start_position.y),
-                            Vector2::new(vector.x, vector.y),
-                        );
-                        let mut has_intersection = false;
-                        let mut min_time: f32 = 1.0;
-                        for connection_data in &connections_data {
-                            if connection_data.layout_index == *layout_index {
-                                let segment = connection_data.segment;
-                                if let Some(time) = segment.cast_ray(
-                                    &Isometry::identity(),
-                                    &ray,
-                                    vector.length(),
-                                    true,
-                                ) {
-                                    if time < 1.0 {
-                                        has_intersection = true;
-                                        min_time = min_time.min(time);
-                                    }
-                                }
-                            }
-                        }
-                        for obstacle_data in &layout_data.obstacles_data {
-                            let ball = obstacle_data.ball;
-                            let isometry = obstacle_data.get_isometry();
-                            if let Some(time) =
-                                ball.cast_ray(&isometry, &ray, vector.length(), true)
-                            {
-                                if time < 1.0 {
-                                    has_intersection = true;
-                                    min_time = min_time.min(time);
-                                }
-                            }
-                        }
-                        if has_intersection {
-                            let vector = vector * min_time;
-                            intersection_point = Some(current_start_position + vector);
-                        }
-                    }
-                    intersection_point
-                };
-
-                let mut next_layout_index = None;
-                if let Some((current_start_index, _)) = current_start {
-                    if is_mouse_button_pressed(MouseButton::Left) {
-                        let mut index = None;
-                        for (i, connection_data) in connections_data.iter().enumerate() {
-                            if connection_data.layout_index == *layout_index {
-                                let point_data = &level_add_data.layouts_data
-                                    [connection_data.layout_index]
-                                    .points_data[connection_data.from_point_index];
-                                if target_position.distance_squared(point_data.position)
-                                    < point_radius * point_radius
-                                {
-                                    index = Some(i);
-                                    break;
-                                }
-                            }
-
-                            let point_data = &level_add_data.layouts_data
-                                [connection_data.layout_index]
-                                .points_data[connection_data.to_point_index];
-                            if let Common {
-                                layout_index: pair_layout_index,
-                                pair_index,
-                            } = point_data.point_type
-                            {
-                                if pair_layout_index == *layout_index {
-                                    let point_data = &level_add_data.layouts_data
-                                        [pair_layout_index]
-                                        .points_data[pair_index];
-                                    if target_position.distance_squared(point_data.position)
-                                        < point_radius * point_radius
-                                    {
-                                        index = Some(i);
-                                        break;
-                                    }
-                                }
-                            }
-                        }
-                        if let Some(index) = index {
-                            connections_data.truncate(index);
-                            if let Some(connection_data) = connections_data.last() {
-                                let last_point_data = &level_add_data.layouts_data
-                                    [connection_data.layout_index]
-                                    .points_data[connection_data.to_point_index];
-                                if let Common {
-                                    layout_index: pair_layout_index,
-                                    pair_index: _,
-                                } = last_point_data.point_type
-                                {
-                                    next_game_state = Some(GameState::Level {
-                                        level_index: *level_index,
-                                        layout_index: pair_layout_index,
-                                    });
-                                }
-                            } else {
-                                for (another_layout_index, layout_data) in
-                                    level_add_data.layouts_data.iter().enumerate()
-                                {
-                                    if layout_data.start_point_index != None
-                                        && another_layout_index != *layout_index
-                                    {
-                                        next_game_state = Some(GameState::Level {
-                                            level_index: *level_index,
-                                            layout_index: another_layout_index,
-                                        });
-                                    }
-                                }
-                            }
-                        }
-                    }
-
-                    if is_mouse_button_released(MouseButton::Left) && intersection_point == None {
-                        if let Some(finish_point_index) = layout_data.finish_point_index {
-                            if target_position.distance_squared(
-                                layout_data.points_data[finish_point_index].position,
-                            ) < point_radius * point_radius
-                            {
-                                if level_data.win_count <= connections_data.len() {
-                                    let from_position =
-                                        layout_data.points_data[current_start_index].position;
-                                    let to_position =
-                                        layout_data.points_data[finish_point_index].position;
-                                    connections_data.push(ConnectionData {
-                                        layout_index: *layout_index,
-                                        from_point_index: current_start_index,
-                                        to_point_index: finish_point_index,
-                                        segment: Segment::new(
-                                            Point2::new(from_position.x, from_position.y),
-                                            Point2::new(to_position.x, to_position.y),
-                                        ),
-                                    });
-                                    if let Common {
-                                        layout_index: pair_layout_index,
-                                        pair_index: _,
-                                    } = layout_data.points_data[finish_point_index].point_type
-                                    {
-                                        if pair_layout_index != *layout_index {
-                                            next_layout_index = Some(pair_layout_index);
-                                        }
-                                    }
-                                } else {
-                                    println!(
-                                        "not enough {}/{}",
-                                        connections_data.len(),
-                                        level_data.win_count
-                                    );
-                                }
-                            }
-                        }
-                        for (i, point_data) in layout_data.points_data.iter().enumerate() {
-                            if i != current_start_index
-                                && target_position.distance_squared(point_data.position)
-                                    < point_radius * point_radius
-                            {
-                                if !connections_data.iter().any(|elem| {
-                                    elem.layout_index == *layout_index && elem.from_point_index == i
-                                        || elem.to_point_index == i
-                                }) {
-                                    let from_position =
-                                        layout_data.points_data[current_start_index].position;
-                                    let to_position = layout_data.points_data[i].position;
-                                    connections_data.push(ConnectionData {
-                                        layout_index: *layout_index,
-                                        from_point_index: current_start_index,
-                                        to_point_index: i,
-                                        segment: Segment::new(
-                                            Point2::new(from_position.x, from_position.y),
-                                            Point2::new(to_position.x, to_position.y),
-                                        ),
-                                    });
-                                    if let Common {
-                                        layout_index: pair_layout_index,
-                                        pair_index: _,
-                                    } = layout_data.points_data[i].point_type
-                                    {
-                                        if pair_layout_index != *layout_index {
-                                            next_layout_index = Some(pair_layout_index);
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
-
-                if let Some(next_layout_index) = next_layout_index {
-                    next_game_state = Some(GameState::Level {
-                        level_index: *level_index,
-                        layout_index: next_layout_index,
-                    });
-                }
-
-                let is_win = {
-                    if let Some(connection_data) = connections_data.last() {
-                        let last_point = &level_add_data.layouts_data[connection_data.layout_index]
-                            .points_data[connection_data.to_point_index];
-                        last_point.point_type == Finish
-                    } else {
-                        false
-                    }
-                };
-
-                for connection_data in &connections_data {
-                    if connection_data.layout_index == *layout_index {
-                        let from_position =
-                            layout_data.points_data[connection_data.from_point_index].position;
-                        let to_position =
-                            layout_data.points_data[connection_data.to_point_index].position;
-                        draw_line(
-                            from_position.x,
-                            from_position.y,
-                            to_position.x,
-                            to_position.y,
-                            0.1,
-                            VIOLET,
-                        );
-                    }
-                }
-
-                if let Some((_, current_start_position)) = current_start {
-                    draw_line(
-                        current_start_position.x,
-                        current_start_position.y,
-                        target_position.x,
-                        target_position.y,
-                        0.1,
-                        RED,
-                    );
-
-                    if let Some(intersection_point) = intersection_point {
-                        draw_line(
-                            intersection_point.x,
-                            intersection_point.y,
-                            target_position.x,
-                            target_position.y,
-                            0.1,
-                            DARKBLUE,
-                        );
-                    }
-                }
-
-                egui_macroquad::ui(|egui_ctx| {
-                    egui::Window::new("GMTK Game Jam 2021").show(egui_ctx, |ui| {
-                        ui.label(format!(
-                            "Playing level: '{}. {}'\nProgress:{}/{}",
-                            level_index + 1,
-                            level_data.name,
-                            connections_data.len().min(level_data.win_count),
-                            level_data.win_count,
-                        ));
-                        if ui.button("Exit to Main Menu").clicked() {
-                            next_game_state = Some(GameState::MainMenu);
-                        }
-                    });
-                    if is_win {
-                        egui::Window::new("Win!").show(egui_ctx, |ui| {
-                            ui.label("Great Success!");
-                            let next_level_index = *level_index + 1;
-                            if next_level_index < game_data.levels.len() {
-                                if ui.button("Next Level").clicked() {
-                                    next_game_state = Some(GameState::LevelPreparing {
-                                        level_index: next_level_index,
-                                    });
-                                }
-                            } else {
-                                ui.label("Thank you for playing!");
-                                if ui.button("Exit to Main Menu").clicked() {
-                                    next_game_state = Some(GameState::MainMenu);
-                                }
-                            }
-                        });
-                    }
-                });
-
-                if let Some(state) = next_game_state {
-                    game_state = state;
+    context.apply_settings();
+
+    let start_state: Box<dyn State> = match start_level {
+        Some((level_index, layout_index)) => Box::new(LevelState::new(
+            &context.game_data,
+            level_index,
+            layout_index,
+        )),
+        None => Box::new(MainMenuState::new()),
+    };
+    let mut state_stack = StateStack::new(start_state, &mut context);
+
+    loop {
+        context.get_renderer().draw_background(&context.camera);
+        context.shown_rect = None;
+
+        if data_watcher.is_changed(get_time()) {
+            match GameData::load_from_file_blocking(file_name) {
+                Ok(new_game_data) if new_game_data.errors.is_empty() => {
+                    let mut data_warnings = new_game_data.warnings.clone();
+                    let renderers =
+                        load_renderers(&new_game_data, file_name, &mut data_warnings).await;
+                    let audio = Audio::load(&new_game_data, file_name, &mut data_warnings).await;
+                    state_stack.change_game_data(&mut context, new_game_data);
+                    data_watcher = DataWatcher::new(&context.game_data.file_names, get_time());
+                    context.data_errors.clear();
+                    context.data_warnings = data_warnings;
+                    reload_assets(&mut context, renderers, audio);
                 }
+                Ok(new_game_data) => context.data_errors = new_game_data.errors,
+                Err(error) => context.data_errors = vec![error],
             }
-
-            GameState::Quit => {
-                break 'game_loop;
+        }
+
+        let mut transition = Transition::None;
+        egui_macroquad::ui(|egui_ctx| {
+            show_data_warnings(egui_ctx, &context.data_warnings);
+            show_data_errors(egui_ctx, &context.data_errors);
+            transition = state_stack.update(&mut context, egui_ctx);
+        });
+        if !state_stack.apply(transition, &mut context) {
+            // replays are saved when their level is left
+            context.save_progress();
+            context.save_settings();
+            break;
+        }
+        state_stack.draw(&mut context);
+
+        if context.game_data.screen.scaling_policy == ScalingPolicy::Letterbox {
+            if let Some(shown_rect) = context.shown_rect {
+                draw_letterbox_bars(&context.camera, shown_rect);
             }
-        };
+        }
 
         egui_macroquad::draw();
         next_frame().await
     }
 }
 
-pub fn update_screen_size(camera: &mut Camera2D, virtual_size: Vec2) {
-    // TODO: add delay and test for new screen size
+// the first theme that loads is the default one, the built-in theme is used without any
+pub async fn load_renderers(
+    game_data: &GameData,
+    game_data_file_name: &str,
+    data_errors: &mut Vec<String>,
+) -> Vec<Renderer> {
+    let mut renderers = Vec::new();
+    for theme_file_name in game_data.get_theme_file_names(game_data_file_name) {
+        match Renderer::load_from_file(&theme_file_name).await {
+            Ok(renderer) => renderers.push(renderer),
+            Err(error) => {
+                println!("{}", error);
+                data_errors.push(error);
+            }
+        }
+    }
+    if renderers.is_empty() {
+        renderers.push(Renderer::new(ThemeData::default()));
+    }
+    renderers
+}
+
+// the music that was playing continues from the new file
+fn reload_assets(context: &mut GameContext, renderers: Vec<Renderer>, audio: Audio) {
+    let music_file_name = context.audio.get_music_file_name().map(str::to_string);
+    context.audio.play_music(None, &context.settings_data);
+    context.renderers = renderers;
+    context.audio = audio;
+    context.apply_settings();
+    context
+        .audio
+        .play_music(music_file_name.as_deref(), &context.settings_data);
+}
 
-    let real_screen_size = vec2(screen_width(), screen_height());
+pub fn show_data_errors(egui_ctx: &egui::CtxRef, data_errors: &[String]) {
+    if data_errors.is_empty() {
+        return;
+    }
+    egui::Window::new("Game data not reloaded").show(egui_ctx, |ui| {
+        for data_error in data_errors {
+            ui.colored_label(egui::Color32::RED, data_error);
+        }
+        ui.label("Fix the files and save them again, until then the last loaded data is used.");
+    });
+}
 
-    let real_aspect_ratio = real_screen_size.x / real_screen_size.y;
-    let target_aspect_ratio = virtual_size.x / virtual_size.y;
-    let virtual_screen_size = if target_aspect_ratio < real_aspect_ratio {
-        vec2(real_aspect_ratio * virtual_size.y, virtual_size.y)
-    } else {
-        vec2(virtual_size.x, virtual_size.x / real_aspect_ratio)
-    };
+pub fn show_data_warnings(egui_ctx: &egui::CtxRef, data_warnings: &[String]) {
+    if data_warnings.is_empty() {
+        return;
+    }
+    egui::Window::new("Game data problems").show(egui_ctx, |ui| {
+        for data_warning in data_warnings {
+            ui.colored_label(egui::Color32::YELLOW, data_warning);
+        }
+        ui.label("The game runs with the rest of the data.");
+    });
+}
 
-    camera.zoom = vec2(2.0 / virtual_screen_size.x, -2.0 / virtual_screen_size.y);
-    camera.target = (vec2(virtual_size.x, virtual_size.y) - Vec2::ONE) / 2.0;
+// in screen space, so layout transitions and shaking do not move the bars
+pub fn draw_letterbox_bars(camera: &Camera2D, shown_rect: Rect) {
+    set_default_camera();
+    let screen_size = vec2(screen_width(), screen_height());
+    let scale = (screen_size.x / shown_rect.w).min(screen_size.y / shown_rect.h);
+    let shown_size = vec2(shown_rect.w, shown_rect.h) * scale;
+    let shown_position = (screen_size - shown_size) / 2.0;
+    draw_rectangle(0.0, 0.0, shown_position.x, screen_size.y, BLACK);
+    draw_rectangle(
+        shown_position.x + shown_size.x,
+        0.0,
+        shown_position.x,
+        screen_size.y,
+        BLACK,
+    );
+    draw_rectangle(0.0, 0.0, screen_size.x, shown_position.y, BLACK);
+    draw_rectangle(
+        0.0,
+        shown_position.y + shown_size.y,
+        screen_size.x,
+        shown_position.y,
+        BLACK,
+    );
     set_camera(camera);
 }
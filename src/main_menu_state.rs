@@ -0,0 +1,104 @@
+use macroquad::prelude::*;
+
+use crate::game_state::{start_endless_level, GameContext, State, Transition};
+use crate::level_select;
+use crate::level_state::LevelState;
+use crate::replay_state::ReplayState;
+use crate::settings_state::SettingsState;
+use crate::toast::{Toast, MESSAGE_TOAST_DURATION};
+use gmtk_jam_2021::replay::ReplayData;
+
+pub struct MainMenuState {
+    replay_file_names: Vec<String>,
+    toast: Option<Toast>, // why the endless mode or a replay did not start
+}
+
+impl MainMenuState {
+    pub fn new() -> MainMenuState {
+        MainMenuState {
+            replay_file_names: Vec::new(),
+            toast: None,
+        }
+    }
+}
+
+impl State for MainMenuState {
+    fn on_enter(&mut self, context: &mut GameContext) {
+        context.audio.play_music(None, &context.settings_data);
+        // the replay of the level just left is in the list too
+        self.replay_file_names = ReplayData::get_replay_file_names();
+    }
+
+    fn update(&mut self, context: &mut GameContext, egui_ctx: &egui::CtxRef) -> Transition {
+        let mut transition = Transition::None;
+        let mut is_endless_started = false;
+        let mut error = None;
+        egui::Window::new("GMTK Game Jam 2021").show(egui_ctx, |ui| {
+            ui.label("Select level:");
+            if let Some(index) = level_select::show_level_select(
+                ui,
+                &context.game_data,
+                &context.levels_additional_data,
+                &context.progress_data,
+                &context.renderers[context.renderer_index].theme_data,
+                &mut context.level_select_chapter_index,
+                &mut context.level_select_page_index,
+            ) {
+                transition =
+                    Transition::Reset(Box::new(LevelState::new(&context.game_data, index, None)));
+            }
+            ui.separator();
+            ui.horizontal(|ui| {
+                if ui.button("Endless").clicked() {
+                    is_endless_started = true;
+                }
+                if ui.button("Settings").clicked() {
+                    transition = Transition::Push(Box::new(SettingsState::new()));
+                }
+                if !cfg!(target_arch = "wasm32") && ui.button("Quit").clicked() {
+                    transition = Transition::Quit;
+                }
+            });
+            if ui
+                .checkbox(&mut context.settings_data.is_time_attack, "Time attack")
+                .changed()
+            {
+                context.save_settings();
+            }
+            if !self.replay_file_names.is_empty() {
+                ui.separator();
+                ui.collapsing("Watch replay", |ui| {
+                    for file_name in self.replay_file_names.iter().rev() {
+                        if ui.button(file_name).clicked() {
+                            let replay_state = ReplayData::load_from_file(file_name)
+                                .and_then(|replay| ReplayState::new(&context.game_data, replay));
+                            match replay_state {
+                                Ok(replay_state) => {
+                                    transition = Transition::Reset(Box::new(replay_state))
+                                }
+                                Err(replay_error) => error = Some(replay_error),
+                            }
+                        }
+                    }
+                });
+            }
+        });
+        if is_endless_started {
+            match start_endless_level(context, miniquad::date::now() as u64) {
+                Ok(endless_transition) => transition = endless_transition,
+                Err(endless_error) => error = Some(endless_error),
+            }
+        }
+        if let Some(text) = error {
+            self.toast = Some(Toast::new(text, MESSAGE_TOAST_DURATION, egui_ctx));
+        }
+        if let Some(toast) = self
+            .toast
+            .as_ref()
+            .filter(|toast| toast.is_shown(get_time()))
+        {
+            toast.show(egui_ctx);
+        }
+        transition
+    }
+}
@@ -0,0 +1,103 @@
+use macroquad::prelude::*;
+
+use crate::game_state::{GameContext, State, Transition};
+use crate::level_state::LevelState;
+use crate::main_menu_state::MainMenuState;
+use crate::settings_state::SettingsState;
+use gmtk_jam_2021::game_data::GameData;
+
+// the level under the menus is frozen, the menus close after the data is reloaded
+
+pub struct PauseState {
+    level_index: usize,
+    is_leave_confirmed: bool, // false if leaving loses connections
+}
+
+impl PauseState {
+    pub fn new(level_index: usize, is_leave_confirmed: bool) -> PauseState {
+        PauseState {
+            level_index,
+            is_leave_confirmed,
+        }
+    }
+}
+
+impl State for PauseState {
+    fn update(&mut self, context: &mut GameContext, egui_ctx: &egui::CtxRef) -> Transition {
+        // the pause key only opens the menu, Escape closes it
+        let mut transition = if is_key_pressed(KeyCode::Escape) {
+            Transition::Pop
+        } else {
+            Transition::None
+        };
+        egui::Window::new("Paused").show(egui_ctx, |ui| {
+            if ui.button("Resume").clicked() {
+                transition = Transition::Pop;
+            }
+            if ui.button("Restart Level").clicked() {
+                let level_state = LevelState::new(&context.game_data, self.level_index, None);
+                transition = get_leave_transition(
+                    self.is_leave_confirmed,
+                    Transition::Reset(Box::new(level_state)),
+                );
+            }
+            if ui.button("Settings").clicked() {
+                transition = Transition::Push(Box::new(SettingsState::new()));
+            }
+            if ui.button("Level Select").clicked() {
+                transition = get_leave_transition(
+                    self.is_leave_confirmed,
+                    Transition::Reset(Box::new(MainMenuState::new())),
+                );
+            }
+            if !cfg!(target_arch = "wasm32") && ui.button("Quit Game").clicked() {
+                transition = get_leave_transition(self.is_leave_confirmed, Transition::Quit);
+            }
+        });
+        transition
+    }
+
+    fn on_game_data_changed(&mut self, _context: &mut GameContext, _: &GameData) -> bool {
+        false
+    }
+}
+
+pub struct LeaveConfirmationState {
+    transition: Option<Transition>, // what happens when the player leaves
+}
+
+impl State for LeaveConfirmationState {
+    fn update(&mut self, _context: &mut GameContext, egui_ctx: &egui::CtxRef) -> Transition {
+        let mut is_left = false;
+        let mut is_stayed = is_key_pressed(KeyCode::Escape);
+        egui::Window::new("Leave the level?").show(egui_ctx, |ui| {
+            ui.label("The connections made so far will be lost.");
+            ui.horizontal(|ui| {
+                is_left = ui.button("Leave").clicked();
+                is_stayed |= ui.button("Stay").clicked();
+            });
+        });
+        if is_left {
+            self.transition.take().unwrap_or(Transition::Pop)
+        } else if is_stayed {
+            Transition::Pop
+        } else {
+            Transition::None
+        }
+    }
+
+    fn on_game_data_changed(&mut self, _context: &mut GameContext, _: &GameData) -> bool {
+        false
+    }
+}
+
+// leaving an unfinished level loses its connections, the player confirms that first
+pub fn get_leave_transition(is_leave_confirmed: bool, transition: Transition) -> Transition {
+    if is_leave_confirmed {
+        transition
+    } else {
+        Transition::Push(Box::new(LeaveConfirmationState {
+            transition: Some(transition),
+        }))
+    }
+}
@@ -0,0 +1,151 @@
+use std::collections::BTreeMap;
+
+use ron::ser::PrettyConfig;
+use serde::{Deserialize, Serialize};
+
+use crate::game_data::{GameData, UnlockRequirement};
+use crate::storage;
+
+pub const PROGRESS_FILE_NAME: &str = "progress.data";
+// the time attack grades, as multiples of the par time of the level
+pub const GOLD_PAR_FACTOR: f32 = 1.0;
+pub const SILVER_PAR_FACTOR: f32 = 1.5;
+pub const BRONZE_PAR_FACTOR: f32 = 2.0;
+
+// levels are stored by name so the progress survives reordering of game.data
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProgressData {
+    pub levels: BTreeMap<String, LevelProgress>,
+}
+
+#[derive(Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LevelProgress {
+    pub is_completed: bool,
+    pub best_connections_count: Option<usize>,
+    // results of the time attack, in seconds
+    pub best_time: Option<f32>,
+    pub best_grade: Option<Grade>,
+    pub best_score: Option<u32>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Serialize, Deserialize)]
+pub enum Grade {
+    Bronze,
+    Silver,
+    Gold,
+}
+
+impl Grade {
+    // None if the level was finished too slow for any grade
+    pub fn from_time(time: f32, par_time: f32) -> Option<Grade> {
+        if time <= par_time * GOLD_PAR_FACTOR {
+            Some(Grade::Gold)
+        } else if time <= par_time * SILVER_PAR_FACTOR {
+            Some(Grade::Silver)
+        } else if time <= par_time * BRONZE_PAR_FACTOR {
+            Some(Grade::Bronze)
+        } else {
+            None
+        }
+    }
+}
+
+impl ProgressData {
+    // a missing or broken file means no progress yet
+    pub fn load_from_file(file_name: &str) -> ProgressData {
+        match storage::read(file_name) {
+            Some(bytes) => ron::de::from_bytes(&bytes).unwrap_or_else(|error| {
+                println!("Progress '{}' not parsed: {}", file_name, error);
+                ProgressData::default()
+            }),
+            None => ProgressData::default(),
+        }
+    }
+
+    pub fn save_to_file(&self, file_name: &str) -> Result<(), String> {
+        let pretty_config = PrettyConfig::new().with_separate_tuple_members(true);
+        let text = ron::ser::to_string_pretty(self, pretty_config)
+            .map_err(|error| format!("Progress serialization failed: {}", error))?;
+        storage::write(file_name, &text)
+    }
+
+    pub fn get_level_progress(&self, level_name: &str) -> Option<&LevelProgress> {
+        self.levels.get(level_name)
+    }
+
+    pub fn is_level_completed(&self, level_name: &str) -> bool {
+        self.get_level_progress(level_name)
+            .is_some_and(|level_progress| level_progress.is_completed)
+    }
+
+    pub fn complete_level(&mut self, level_name: &str, connections_count: usize) {
+        let level_progress = self.levels.entry(level_name.to_string()).or_default();
+        level_progress.is_completed = true;
+        level_progress.best_connections_count = Some(
+            level_progress
+                .best_connections_count
+                .map_or(connections_count, |count| count.min(connections_count)),
+        );
+    }
+
+    // returns true if the time is a new best time of the level
+    pub fn record_time(&mut self, level_name: &str, time: f32, grade: Option<Grade>) -> bool {
+        let level_progress = self.levels.entry(level_name.to_string()).or_default();
+        level_progress.best_grade = level_progress.best_grade.max(grade);
+        let is_best_time = level_progress
+            .best_time
+            .map_or(true, |best_time| time < best_time);
+        if is_best_time {
+            level_progress.best_time = Some(time);
+        }
+        is_best_time
+    }
+
+    // returns true if the score is a new best score of the level
+    pub fn record_score(&mut self, level_name: &str, score: u32) -> bool {
+        let level_progress = self.levels.entry(level_name.to_string()).or_default();
+        let is_best_score = level_progress
+            .best_score
+            .map_or(true, |best_score| best_score < score);
+        if is_best_score {
+            level_progress.best_score = Some(score);
+        }
+        is_best_score
+    }
+
+    pub fn is_chapter_unlocked(&self, game_data: &GameData, chapter_index: usize) -> bool {
+        match &game_data.chapters[chapter_index].unlock_requirement {
+            UnlockRequirement::Always => true,
+            UnlockRequirement::CompletedLevels(count) => {
+                let completed_count = game_data
+                    .levels
+                    .iter()
+                    .filter(|level_data| self.is_level_completed(&level_data.name))
+                    .count();
+                *count <= completed_count
+            }
+            UnlockRequirement::CompletedPack(title) => {
+                // a pack without levels or with an unknown title never unlocks it
+                let mut chapters = game_data
+                    .chapters
+                    .iter()
+                    .filter(|chapter_data| chapter_data.title == *title)
+                    .peekable();
+                chapters.peek().is_some()
+                    && chapters.all(|chapter_data| {
+                        chapter_data
+                            .get_level_indices()
+                            .all(|index| self.is_level_completed(&game_data.levels[index].name))
+                    })
+            }
+        }
+    }
+
+    pub fn is_level_unlocked(&self, game_data: &GameData, level_index: usize) -> bool {
+        game_data
+            .get_chapter_index(level_index)
+            .is_some_and(|chapter_index| self.is_chapter_unlocked(game_data, chapter_index))
+    }
+}
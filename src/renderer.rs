@@ -0,0 +1,251 @@
+use macroquad::prelude::*;
+
+use gmtk_jam_2021::game_data::{ConnectionData, LayoutAdditionalData, PointType};
+use gmtk_jam_2021::simulation::POINT_RADIUS;
+use gmtk_jam_2021::theme::ThemeData;
+
+pub const LABEL_FONT_SIZE: u16 = 48;
+pub const PAIR_LABEL_SIZE: f32 = 0.4;
+pub const PARTNER_LABEL_SIZE: f32 = 0.3;
+
+// draws levels with the colors and sprites of a theme,
+// shapes are used where the theme has no sprite
+pub struct Renderer {
+    pub theme_data: ThemeData,
+    background_texture: Option<Texture2D>,
+    point_texture: Option<Texture2D>,
+    start_texture: Option<Texture2D>,
+    finish_texture: Option<Texture2D>,
+    obstacle_texture: Option<Texture2D>,
+}
+
+impl Renderer {
+    pub fn new(theme_data: ThemeData) -> Renderer {
+        Renderer {
+            theme_data,
+            background_texture: None,
+            point_texture: None,
+            start_texture: None,
+            finish_texture: None,
+            obstacle_texture: None,
+        }
+    }
+
+    pub async fn load_from_file(file_name: &str) -> Result<Renderer, String> {
+        let bytes = load_file(file_name)
+            .await
+            .map_err(|error| format!("Theme '{}' not read: {}", file_name, error))?;
+        let theme_data = ThemeData::from_bytes(&bytes)?;
+        let mut renderer = Renderer::new(theme_data);
+        renderer.background_texture =
+            load_theme_texture(file_name, &renderer.theme_data.background_texture).await;
+        renderer.point_texture =
+            load_theme_texture(file_name, &renderer.theme_data.point_texture).await;
+        renderer.start_texture =
+            load_theme_texture(file_name, &renderer.theme_data.start_texture).await;
+        renderer.finish_texture =
+            load_theme_texture(file_name, &renderer.theme_data.finish_texture).await;
+        renderer.obstacle_texture =
+            load_theme_texture(file_name, &renderer.theme_data.obstacle_texture).await;
+        Ok(renderer)
+    }
+
+    // the background covers the whole window, the camera is restored afterwards
+    pub fn draw_background(&self, camera: &Camera2D) {
+        clear_background(Color::from(self.theme_data.background_color));
+        if let Some(texture) = self.background_texture {
+            set_default_camera();
+            draw_texture_ex(
+                texture,
+                0.0,
+                0.0,
+                WHITE,
+                DrawTextureParams {
+                    dest_size: Some(vec2(screen_width(), screen_height())),
+                    ..Default::default()
+                },
+            );
+            set_camera(camera);
+        }
+    }
+
+    fn draw_circle_or_texture(
+        &self,
+        position: Vec2,
+        radius: f32,
+        color: [f32; 4],
+        texture: Option<Texture2D>,
+    ) {
+        match texture {
+            Some(texture) => draw_texture_ex(
+                texture,
+                position.x - radius,
+                position.y - radius,
+                WHITE,
+                DrawTextureParams {
+                    dest_size: Some(vec2(radius * 2.0, radius * 2.0)),
+                    ..Default::default()
+                },
+            ),
+            None => draw_circle(position.x, position.y, radius, Color::from(color)),
+        }
+    }
+
+    pub fn draw_layout(
+        &self,
+        layout_data: &LayoutAdditionalData,
+        connections_data: &[ConnectionData],
+        layout_index: usize,
+    ) {
+        let theme_data = &self.theme_data;
+        draw_rectangle_lines(
+            -0.5,
+            -0.5,
+            layout_data.size.x,
+            layout_data.size.y,
+            theme_data.line_thickness,
+            Color::from(theme_data.border_color),
+        );
+        for y in 0..layout_data.size.y as i32 {
+            for x in 0..layout_data.size.x as i32 {
+                draw_circle(
+                    x as f32,
+                    y as f32,
+                    theme_data.grid_dot_radius,
+                    Color::from(theme_data.grid_color),
+                );
+            }
+        }
+
+        for (point_index, point_data) in layout_data.points_data.iter().enumerate() {
+            let (color, texture) = if layout_data.start_point_index == Some(point_index) {
+                (theme_data.start_color, self.start_texture)
+            } else if layout_data.finish_point_index == Some(point_index) {
+                (theme_data.finish_color, self.finish_texture)
+            } else {
+                (
+                    theme_data.get_pair_color(point_data.pair_id),
+                    self.point_texture,
+                )
+            };
+            self.draw_circle_or_texture(point_data.position, POINT_RADIUS, color, texture);
+        }
+        for point_data in &layout_data.points_data {
+            if let Some(pair_id) = point_data.pair_id {
+                self.draw_label(
+                    &pair_id.to_string(),
+                    point_data.position,
+                    PAIR_LABEL_SIZE,
+                    Color::from(theme_data.pair_label_color),
+                );
+            }
+            // the number of the layout where the pair continues
+            if let PointType::Common {
+                layout_index: pair_layout_index,
+                ..
+            } = point_data.point_type
+            {
+                if pair_layout_index != layout_index {
+                    self.draw_label(
+                        &format!("L{}", pair_layout_index + 1),
+                        point_data.position + vec2(POINT_RADIUS, -POINT_RADIUS) * 1.6,
+                        PARTNER_LABEL_SIZE,
+                        Color::from(theme_data.get_pair_color(point_data.pair_id)),
+                    );
+                }
+            }
+        }
+        for obstacle_data in &layout_data.obstacles_data {
+            self.draw_circle_or_texture(
+                obstacle_data.position,
+                obstacle_data.radius,
+                theme_data.obstacle_color,
+                self.obstacle_texture,
+            );
+        }
+
+        for connection_data in connections_data {
+            if connection_data.layout_index == layout_index {
+                let from_position =
+                    layout_data.points_data[connection_data.from_point_index].position;
+                let to_position = layout_data.points_data[connection_data.to_point_index].position;
+                draw_line(
+                    from_position.x,
+                    from_position.y,
+                    to_position.x,
+                    to_position.y,
+                    theme_data.line_thickness,
+                    Color::from(theme_data.connection_color),
+                );
+            }
+        }
+    }
+
+    // text centered at the position, the size is the text height in world units
+    fn draw_label(&self, text: &str, position: Vec2, size: f32, color: Color) {
+        let font_scale = size / LABEL_FONT_SIZE as f32;
+        let dimensions = measure_text(text, None, LABEL_FONT_SIZE, font_scale);
+        draw_text_ex(
+            text,
+            position.x - dimensions.width / 2.0,
+            position.y - dimensions.height / 2.0 + dimensions.offset_y,
+            TextParams {
+                font_size: LABEL_FONT_SIZE,
+                font_scale,
+                color,
+                ..Default::default()
+            },
+        );
+    }
+
+    pub fn draw_current_layout_frame(&self, layout_data: &LayoutAdditionalData) {
+        draw_rectangle_lines(
+            -0.5,
+            -0.5,
+            layout_data.size.x,
+            layout_data.size.y,
+            self.theme_data.line_thickness,
+            Color::from(self.theme_data.current_layout_color),
+        );
+    }
+
+    // the line from the current point to the mouse, the part behind an intersection is blocked
+    pub fn draw_aim(&self, from: Vec2, to: Vec2, intersection_point: Option<Vec2>) {
+        let thickness = self.theme_data.line_thickness;
+        draw_line(
+            from.x,
+            from.y,
+            to.x,
+            to.y,
+            thickness,
+            Color::from(self.theme_data.aim_color),
+        );
+        if let Some(intersection_point) = intersection_point {
+            draw_line(
+                intersection_point.x,
+                intersection_point.y,
+                to.x,
+                to.y,
+                thickness,
+                Color::from(self.theme_data.blocked_aim_color),
+            );
+        }
+    }
+}
+
+async fn load_theme_texture(theme_file_name: &str, path: &Option<String>) -> Option<Texture2D> {
+    let path = path.as_ref()?;
+    let texture_file_name = std::path::Path::new(theme_file_name)
+        .parent()
+        .unwrap_or_else(|| std::path::Path::new(""))
+        .join(path)
+        .to_string_lossy()
+        .to_string();
+    match load_texture(&texture_file_name).await {
+        Ok(texture) => Some(texture),
+        Err(error) => {
+            println!("Texture '{}' not loaded: {}", texture_file_name, error);
+            None
+        }
+    }
+}
@@ -0,0 +1,115 @@
+use std::fs::File;
+
+use ron::ser::PrettyConfig;
+use serde::{Deserialize, Serialize};
+
+use crate::game_data::GameData;
+
+pub const REPLAYS_DIRECTORY: &str = "replays";
+pub const REPLAY_FILE_EXTENSION: &str = "replay";
+
+#[derive(Default, Clone, Serialize, Deserialize)]
+pub struct ReplayData {
+    pub level_index: usize,
+    pub level_name: String,
+    pub actions: Vec<ReplayAction>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ReplayAction {
+    pub time: f64, // seconds since the level attempt started
+    pub action_type: ReplayActionType,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub enum ReplayActionType {
+    Connect {
+        layout_index: usize,
+        from_point_index: usize,
+        to_point_index: usize,
+    },
+    Undo {
+        connection_index: usize,
+    },
+}
+
+impl ReplayData {
+    pub fn new(level_index: usize, level_name: &str) -> ReplayData {
+        ReplayData {
+            level_index,
+            level_name: level_name.to_string(),
+            actions: vec![],
+        }
+    }
+
+    pub fn record(&mut self, time: f64, action_type: ReplayActionType) {
+        self.actions.push(ReplayAction { time, action_type });
+    }
+
+    pub fn get_duration(&self) -> f64 {
+        self.actions.last().map_or(0.0, |action| action.time)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<ReplayData, String> {
+        ron::de::from_bytes(bytes).map_err(|error| format!("Replay parsing failed: {}", error))
+    }
+
+    pub fn load_from_file(file_name: &str) -> Result<ReplayData, String> {
+        let bytes = std::fs::read(file_name)
+            .map_err(|error| format!("Replay '{}' not read: {}", file_name, error))?;
+        ReplayData::from_bytes(&bytes)
+    }
+
+    pub fn save_to_file(&self, file_name: &str) -> Result<(), String> {
+        let pretty_config = PrettyConfig::new().with_separate_tuple_members(true);
+        let file = File::create(file_name)
+            .map_err(|error| format!("Replay '{}' not created: {}", file_name, error))?;
+        ron::ser::to_writer_pretty(file, self, pretty_config)
+            .map_err(|error| format!("Replay serialization failed: {}", error))
+    }
+
+    // saves the attempt as "replays/<level number>_<unix time>.replay"
+    pub fn save_to_replays_directory(&self) -> Result<String, String> {
+        let time = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_err(|error| error.to_string())?
+            .as_secs();
+        std::fs::create_dir_all(REPLAYS_DIRECTORY).map_err(|error| error.to_string())?;
+        let file_name = format!(
+            "{}/{}_{}.{}",
+            REPLAYS_DIRECTORY,
+            self.level_index + 1,
+            time,
+            REPLAY_FILE_EXTENSION
+        );
+        self.save_to_file(&file_name)?;
+        Ok(file_name)
+    }
+
+    // the level the replay was recorded on, by name since the levels may have been reordered,
+    // the recorded index wins among the levels with the same name
+    pub fn find_level_index(&self, game_data: &GameData) -> Option<usize> {
+        let is_recorded_level =
+            |level_index: &usize| game_data.levels[*level_index].name == self.level_name;
+        Some(self.level_index)
+            .filter(|level_index| *level_index < game_data.levels.len())
+            .filter(is_recorded_level)
+            .or_else(|| (0..game_data.levels.len()).find(is_recorded_level))
+    }
+
+    pub fn get_replay_file_names() -> Vec<String> {
+        let mut file_names = Vec::new();
+        if let Ok(entries) = std::fs::read_dir(REPLAYS_DIRECTORY) {
+            for entry in entries.flatten() {
+                let path = entry.path();
+                if path.extension().is_some_and(|e| e == REPLAY_FILE_EXTENSION) {
+                    if let Some(file_name) = path.to_str() {
+                        file_names.push(file_name.to_string());
+                    }
+                }
+            }
+        }
+        file_names.sort();
+        file_names
+    }
+}
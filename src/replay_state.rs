@@ -0,0 +1,203 @@
+use macroquad::prelude::*;
+
+use crate::game_state::{find_level_index, GameContext, State, Transition};
+use crate::level_view;
+use crate::main_menu_state::MainMenuState;
+use gmtk_jam_2021::game_data::{ConnectionData, GameData, LevelAdditionalData, LevelData};
+use gmtk_jam_2021::replay::ReplayData;
+use gmtk_jam_2021::simulation;
+use gmtk_jam_2021::spatial_grid::ConnectionsGrid;
+
+pub struct ReplayState {
+    level_index: usize,
+    level_additional_data: LevelAdditionalData,
+    connections_data: Vec<ConnectionData>,
+    connections_grid: ConnectionsGrid,
+    replay_data: ReplayData,
+    actions_count: usize, // how many recorded actions are applied
+    // the first action the rules of the level do not accept, the replay stops before it
+    rejected_action_index: Option<usize>,
+    time: f64,
+    is_paused: bool,
+}
+
+impl ReplayState {
+    // refuses the replay if its level is not in the game data anymore
+    pub fn new(game_data: &GameData, replay_data: ReplayData) -> Result<ReplayState, String> {
+        let level_index = replay_data.find_level_index(game_data).ok_or_else(|| {
+            format!(
+                "The level '{}' of the replay is not found",
+                replay_data.level_name
+            )
+        })?;
+        Ok(ReplayState {
+            level_index,
+            level_additional_data: LevelAdditionalData::new(&game_data.levels[level_index]),
+            connections_data: Vec::new(),
+            connections_grid: ConnectionsGrid::default(),
+            replay_data,
+            actions_count: 0,
+            rejected_action_index: None,
+            time: 0.0,
+            is_paused: false,
+        })
+    }
+
+    fn get_actions_count_at(&self, time: f64) -> usize {
+        self.replay_data
+            .actions
+            .iter()
+            .filter(|action| action.time <= time)
+            .count()
+    }
+
+    fn restart(&mut self) {
+        self.connections_data.clear();
+        self.actions_count = 0;
+        self.rejected_action_index = None;
+    }
+
+    // applies the recorded actions up to actions_count with the same checks as the game,
+    // going back starts over from the empty level
+    fn apply_actions(&mut self, level_data: &LevelData, actions_count: usize) {
+        if actions_count < self.actions_count {
+            self.restart();
+        }
+        while self.actions_count < actions_count && self.rejected_action_index.is_none() {
+            let action = &self.replay_data.actions[self.actions_count];
+            if simulation::simulate_action(
+                level_data,
+                &self.level_additional_data,
+                &mut self.connections_data,
+                &mut self.connections_grid,
+                &action.action_type,
+            ) {
+                self.actions_count += 1;
+            } else {
+                self.rejected_action_index = Some(self.actions_count);
+            }
+        }
+    }
+}
+
+impl State for ReplayState {
+    fn update(&mut self, context: &mut GameContext, egui_ctx: &egui::CtxRef) -> Transition {
+        let mut transition = Transition::None;
+        let level_data = &(context.game_data.levels[self.level_index]);
+        let duration = self.replay_data.get_duration();
+        let mut time = self.time;
+        let mut is_paused = self.is_paused;
+
+        if !is_paused {
+            time = (time + get_frame_time() as f64).min(duration);
+        }
+        let timed_actions_count = self.get_actions_count_at(time);
+        self.apply_actions(level_data, timed_actions_count);
+        if self.rejected_action_index.is_some() {
+            is_paused = true;
+        }
+
+        let actions_count = self.actions_count;
+        let actions_len = self.replay_data.actions.len();
+        let rejected_action_index = self.rejected_action_index;
+        let mut seek_actions_count = None;
+        let mut seek_time = None;
+        egui::Window::new("Replay").show(egui_ctx, |ui| {
+            ui.label(format!(
+                "Watching level: '{}. {}'\nStep: {}/{}",
+                self.level_index + 1,
+                level_data.name,
+                actions_count,
+                actions_len,
+            ));
+            if let Some(index) = rejected_action_index {
+                ui.colored_label(
+                    egui::Color32::RED,
+                    format!("Step {} is against the rules, the replay stops", index + 1),
+                );
+            }
+            ui.horizontal(|ui| {
+                if ui.button("|<").clicked() {
+                    seek_actions_count = Some(0);
+                }
+                if ui.button("<").clicked() {
+                    seek_actions_count = Some(actions_count.saturating_sub(1));
+                }
+                if ui
+                    .button(if is_paused { "Play" } else { "Pause" })
+                    .clicked()
+                {
+                    is_paused = !is_paused;
+                }
+                if ui.button(">").clicked() {
+                    seek_actions_count = Some((actions_count + 1).min(actions_len));
+                }
+                if ui.button(">|").clicked() {
+                    seek_actions_count = Some(actions_len);
+                }
+            });
+            let mut slider_time = time;
+            if ui
+                .add(egui::Slider::new(&mut slider_time, 0.0..=duration).text("sec"))
+                .changed()
+            {
+                seek_time = Some(slider_time);
+            }
+            if ui.button("Exit to Main Menu").clicked() {
+                transition = Transition::Reset(Box::new(MainMenuState::new()));
+            }
+        });
+
+        if let Some(count) = seek_actions_count {
+            time = if 0 < count {
+                self.replay_data.actions[count - 1].time
+            } else {
+                0.0
+            };
+            is_paused = true;
+            self.apply_actions(level_data, count);
+        } else if let Some(new_time) = seek_time {
+            time = new_time;
+            let count = self.get_actions_count_at(time);
+            self.apply_actions(level_data, count);
+        }
+        self.time = time;
+        self.is_paused = is_paused;
+        transition
+    }
+
+    fn draw(&mut self, context: &mut GameContext) {
+        let layout_index = self
+            .level_additional_data
+            .get_current_layout_index(&self.connections_data);
+        let layout_data = &self.level_additional_data.layouts_data[layout_index];
+        context.shown_rect = Some(level_view::update_screen_size(
+            &mut context.camera,
+            layout_data.size,
+            &context.game_data,
+        ));
+        context
+            .get_renderer()
+            .draw_layout(layout_data, &self.connections_data, layout_index);
+    }
+
+    fn on_game_data_changed(
+        &mut self,
+        context: &mut GameContext,
+        new_game_data: &GameData,
+    ) -> bool {
+        match find_level_index(&context.game_data, new_game_data, self.level_index) {
+            Some(new_level_index) => {
+                self.level_index = new_level_index;
+                self.level_additional_data =
+                    LevelAdditionalData::new(&new_game_data.levels[new_level_index]);
+                // the changed level checks the same actions from the start
+                let actions_count = self.get_actions_count_at(self.time);
+                self.restart();
+                self.apply_actions(&new_game_data.levels[new_level_index], actions_count);
+                true
+            }
+            None => false,
+        }
+    }
+}
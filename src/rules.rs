@@ -0,0 +1,125 @@
+use crate::game_data::{ConnectionData, LevelAdditionalData, LevelData, PointId, PointType};
+
+// the rules of the path, the game, the replays and the tools check them here:
+// a point is used once, the partner of a portal is used when the path jumps to it,
+// the finish ends the path and takes win_count connections before it
+
+// why a release of the mouse did not make a connection
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum RejectionReason {
+    NoPoint, // released over nothing
+    PointUsed,
+    BlockedByConnection,
+    BlockedByObstacle,
+    TooClose, // closer than the clearance of the level
+    NotEnoughConnections {
+        connections_count: usize,
+        win_count: usize,
+    },
+    TooManyConnections {
+        connections_count: usize,
+        win_count: usize,
+    },
+    OnlyFinishLeft, // the exact count is reached
+    OutOfMoves,
+}
+
+// the point the path goes on from after the connection, None after the finish
+pub fn get_next_start(
+    level_additional_data: &LevelAdditionalData,
+    connection_data: &ConnectionData,
+) -> Option<PointId> {
+    let point_data = &level_additional_data.layouts_data[connection_data.layout_index].points_data
+        [connection_data.to_point_index];
+    match point_data.point_type {
+        PointType::Common {
+            layout_index,
+            pair_index,
+        } => Some(PointId {
+            layout_index,
+            point_index: pair_index,
+        }),
+        _ => None,
+    }
+}
+
+pub fn is_point_used(
+    level_additional_data: &LevelAdditionalData,
+    connections_data: &[ConnectionData],
+    layout_index: usize,
+    point_index: usize,
+) -> bool {
+    let point_id = PointId {
+        layout_index,
+        point_index,
+    };
+    connections_data.iter().any(|connection_data| {
+        (connection_data.layout_index == layout_index
+            && (connection_data.from_point_index == point_index
+                || connection_data.to_point_index == point_index))
+            || get_next_start(level_additional_data, connection_data) == Some(point_id)
+    })
+}
+
+// None if the level has no move limit
+pub fn get_moves_left(level_data: &LevelData, connections_count: usize) -> Option<usize> {
+    level_data
+        .max_moves
+        .map(|max_moves| max_moves.saturating_sub(connections_count))
+}
+
+pub fn is_finish_allowed(level_data: &LevelData, connections_count: usize) -> bool {
+    if level_data.is_exact_count {
+        connections_count == level_data.win_count
+    } else {
+        level_data.win_count <= connections_count
+    }
+}
+
+// a connection to a point that is not the finish,
+// in the exact count mode nothing but the finish follows the last needed connection
+pub fn is_connection_allowed(level_data: &LevelData, connections_count: usize) -> bool {
+    !level_data.is_exact_count || connections_count < level_data.win_count
+}
+
+// whether the next connection may go to the point, the way to it is not checked
+pub fn check_target(
+    level_data: &LevelData,
+    level_additional_data: &LevelAdditionalData,
+    connections_data: &[ConnectionData],
+    layout_index: usize,
+    point_index: usize,
+) -> Result<(), RejectionReason> {
+    let connections_count = connections_data.len();
+    // the moves are over, the player has to undo
+    if get_moves_left(level_data, connections_count) == Some(0) {
+        return Err(RejectionReason::OutOfMoves);
+    }
+    let point_data = &level_additional_data.layouts_data[layout_index].points_data[point_index];
+    match point_data.point_type {
+        PointType::Finish if is_finish_allowed(level_data, connections_count) => Ok(()),
+        PointType::Finish if level_data.win_count < connections_count => {
+            Err(RejectionReason::TooManyConnections {
+                connections_count,
+                win_count: level_data.win_count,
+            })
+        }
+        PointType::Finish => Err(RejectionReason::NotEnoughConnections {
+            connections_count,
+            win_count: level_data.win_count,
+        }),
+        _ if !is_connection_allowed(level_data, connections_count) => {
+            Err(RejectionReason::OnlyFinishLeft)
+        }
+        _ if is_point_used(
+            level_additional_data,
+            connections_data,
+            layout_index,
+            point_index,
+        ) =>
+        {
+            Err(RejectionReason::PointUsed)
+        }
+        _ => Ok(()),
+    }
+}
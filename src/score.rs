@@ -0,0 +1,72 @@
+use serde::{Deserialize, Serialize};
+
+use crate::game_data::{ConnectionData, LevelAdditionalData, PointType};
+
+// a won level starts with BASE_SCORE, every part of the solution takes some of it,
+// so shorter solutions with fewer moves score more
+pub const BASE_SCORE: f32 = 1000.0;
+pub const LENGTH_PENALTY: f32 = 10.0; // per layout cell
+pub const CONNECTION_PENALTY: f32 = 25.0;
+pub const PORTAL_JUMP_PENALTY: f32 = 15.0;
+pub const UNDO_PENALTY: f32 = 20.0;
+
+#[derive(Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScoreData {
+    pub length: f32, // of all connections, in layout cells
+    pub connections_count: usize,
+    pub portal_jumps_count: usize,
+    pub undos_count: usize,
+}
+
+impl ScoreData {
+    pub fn new(
+        level_additional_data: &LevelAdditionalData,
+        connections_data: &[ConnectionData],
+        undos_count: usize,
+    ) -> ScoreData {
+        ScoreData {
+            length: connections_data
+                .iter()
+                .map(|connection_data| {
+                    let segment = &connection_data.segment;
+                    (segment.b - segment.a).norm()
+                })
+                .sum(),
+            connections_count: connections_data.len(),
+            portal_jumps_count: connections_data
+                .iter()
+                .filter(|connection_data| is_portal_jump(level_additional_data, connection_data))
+                .count(),
+            undos_count,
+        }
+    }
+
+    // in the order of length, connections, portal jumps and undos
+    pub fn get_penalties(&self) -> [f32; 4] {
+        [
+            self.length * LENGTH_PENALTY,
+            self.connections_count as f32 * CONNECTION_PENALTY,
+            self.portal_jumps_count as f32 * PORTAL_JUMP_PENALTY,
+            self.undos_count as f32 * UNDO_PENALTY,
+        ]
+    }
+
+    pub fn get_score(&self) -> u32 {
+        let penalty: f32 = self.get_penalties().iter().sum();
+        (BASE_SCORE - penalty).max(0.0).round() as u32
+    }
+}
+
+// the connection ends at a point whose pair is in another layout
+fn is_portal_jump(
+    level_additional_data: &LevelAdditionalData,
+    connection_data: &ConnectionData,
+) -> bool {
+    let point_data = &level_additional_data.layouts_data[connection_data.layout_index].points_data
+        [connection_data.to_point_index];
+    match point_data.point_type {
+        PointType::Common { layout_index, .. } => layout_index != connection_data.layout_index,
+        _ => false,
+    }
+}
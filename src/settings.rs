@@ -0,0 +1,110 @@
+use ron::ser::PrettyConfig;
+use serde::{Deserialize, Serialize};
+
+use crate::simulation::POINT_RADIUS;
+use crate::storage;
+
+pub const SETTINGS_FILE_NAME: &str = "settings.data";
+pub const MAX_SNAP_RADIUS: f32 = 0.6;
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SettingsData {
+    pub sound_volume: f32,
+    pub music_volume: f32,
+    pub is_muted: bool,
+    // None keeps the window settings of the game data
+    pub is_fullscreen: Option<bool>,
+    // None is the first theme of the game data
+    pub theme_title: Option<String>,
+    // the aim jumps to a free point closer than snap_radius
+    pub is_snap_assist: bool,
+    pub snap_radius: f32,
+    pub is_reduced_motion: bool,
+    // levels are timed and graded by their par times
+    pub is_time_attack: bool,
+    pub bindings: BindingsData,
+}
+
+// names of the keys as macroquad's KeyCode writes them
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BindingsData {
+    pub undo: String,
+    pub toggle_layouts_view: String,
+    pub mute: String,
+    pub pause: String,
+}
+
+impl Default for BindingsData {
+    fn default() -> Self {
+        BindingsData {
+            undo: "Z".to_string(),
+            toggle_layouts_view: "Tab".to_string(),
+            mute: "M".to_string(),
+            pause: "P".to_string(),
+        }
+    }
+}
+
+impl Default for SettingsData {
+    fn default() -> Self {
+        SettingsData {
+            sound_volume: 0.8,
+            music_volume: 0.5,
+            is_muted: false,
+            is_fullscreen: None,
+            theme_title: None,
+            is_snap_assist: true,
+            snap_radius: POINT_RADIUS,
+            is_reduced_motion: false,
+            is_time_attack: false,
+            bindings: BindingsData::default(),
+        }
+    }
+}
+
+impl SettingsData {
+    // a missing or broken file means the default settings
+    pub fn load_from_file(file_name: &str) -> SettingsData {
+        match storage::read(file_name) {
+            Some(bytes) => ron::de::from_bytes(&bytes).unwrap_or_else(|error| {
+                println!("Settings '{}' not parsed: {}", file_name, error);
+                SettingsData::default()
+            }),
+            None => SettingsData::default(),
+        }
+    }
+
+    pub fn save_to_file(&self, file_name: &str) -> Result<(), String> {
+        let pretty_config = PrettyConfig::new().with_separate_tuple_members(true);
+        let text = ron::ser::to_string_pretty(self, pretty_config)
+            .map_err(|error| format!("Settings serialization failed: {}", error))?;
+        storage::write(file_name, &text)
+    }
+
+    pub fn get_sound_volume(&self) -> f32 {
+        if self.is_muted {
+            0.0
+        } else {
+            self.sound_volume
+        }
+    }
+
+    pub fn get_music_volume(&self) -> f32 {
+        if self.is_muted {
+            0.0
+        } else {
+            self.music_volume
+        }
+    }
+
+    // the distance at which the aim snaps to a point, without assist the mouse must be over it
+    pub fn get_snap_radius(&self) -> f32 {
+        if self.is_snap_assist {
+            self.snap_radius.clamp(POINT_RADIUS, MAX_SNAP_RADIUS)
+        } else {
+            0.0
+        }
+    }
+}
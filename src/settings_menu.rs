@@ -0,0 +1,110 @@
+use macroquad::prelude::*;
+
+use crate::input;
+use crate::renderer::Renderer;
+use gmtk_jam_2021::game_data::GameData;
+use gmtk_jam_2021::settings::{SettingsData, MAX_SNAP_RADIUS};
+use gmtk_jam_2021::simulation::POINT_RADIUS;
+
+// the next key pressed becomes the binding, Escape keeps the old one,
+// returns true while a key is awaited so Escape does not close the menu
+pub fn capture_binding(
+    settings_data: &mut SettingsData,
+    waiting_binding_index: &mut Option<usize>,
+) -> bool {
+    let binding_index = match *waiting_binding_index {
+        Some(binding_index) => binding_index,
+        None => return false,
+    };
+    if is_key_pressed(KeyCode::Escape) {
+        *waiting_binding_index = None;
+    } else if let Some(key_code) = input::get_pressed_bindable_key() {
+        *input::get_bindings(&mut settings_data.bindings)[binding_index].1 =
+            input::get_key_name(key_code);
+        *waiting_binding_index = None;
+    }
+    true
+}
+
+// the settings screen, shared by the main menu and the pause menu,
+// returns true when the player goes back
+pub fn show_settings(
+    ui: &mut egui::Ui,
+    settings_data: &mut SettingsData,
+    game_data: &GameData,
+    renderers: &[Renderer],
+    renderer_index: usize,
+    waiting_binding_index: &mut Option<usize>,
+) -> bool {
+    ui.heading("Audio");
+    ui.add(egui::Slider::new(&mut settings_data.sound_volume, 0.0..=1.0).text("Sounds"));
+    ui.add(egui::Slider::new(&mut settings_data.music_volume, 0.0..=1.0).text("Music"));
+    ui.checkbox(&mut settings_data.is_muted, "Mute");
+
+    ui.separator();
+    ui.heading("Display");
+    let mut is_fullscreen = settings_data
+        .is_fullscreen
+        .unwrap_or(game_data.window.fullscreen);
+    if ui.checkbox(&mut is_fullscreen, "Fullscreen").changed() {
+        settings_data.is_fullscreen = Some(is_fullscreen);
+    }
+    if 1 < renderers.len() {
+        ui.horizontal_wrapped(|ui| {
+            ui.label("Theme:");
+            for (index, renderer) in renderers.iter().enumerate() {
+                let title = &renderer.theme_data.title;
+                if ui
+                    .selectable_label(renderer_index == index, title)
+                    .clicked()
+                {
+                    settings_data.theme_title = Some(title.clone());
+                }
+            }
+        });
+    }
+
+    ui.separator();
+    ui.heading("Gameplay");
+    ui.checkbox(&mut settings_data.is_snap_assist, "Snap assist");
+    if settings_data.is_snap_assist {
+        ui.add(
+            egui::Slider::new(
+                &mut settings_data.snap_radius,
+                POINT_RADIUS..=MAX_SNAP_RADIUS,
+            )
+            .text("Snap radius"),
+        );
+    }
+    ui.checkbox(&mut settings_data.is_reduced_motion, "Reduce motion");
+
+    ui.separator();
+    ui.heading("Controls");
+    egui::Grid::new("bindings").show(ui, |ui| {
+        for (index, (title, key_name)) in input::get_bindings(&mut settings_data.bindings)
+            .iter()
+            .enumerate()
+        {
+            ui.label(*title);
+            let text = if *waiting_binding_index == Some(index) {
+                "Press a key...".to_string()
+            } else {
+                key_name.to_string()
+            };
+            if ui.button(text).clicked() {
+                *waiting_binding_index = Some(index);
+            }
+            ui.end_row();
+        }
+    });
+
+    ui.separator();
+    let mut is_back = false;
+    ui.horizontal(|ui| {
+        if ui.button("Reset to Defaults").clicked() {
+            *settings_data = SettingsData::default();
+        }
+        is_back = ui.button("Back").clicked();
+    });
+    is_back
+}
@@ -0,0 +1,51 @@
+use macroquad::prelude::*;
+
+use crate::game_state::{GameContext, State, Transition};
+use crate::settings_menu;
+
+// the settings screen, pushed on top of the main menu or the pause menu
+pub struct SettingsState {
+    waiting_binding_index: Option<usize>, // the binding that gets the next pressed key
+}
+
+impl SettingsState {
+    pub fn new() -> SettingsState {
+        SettingsState {
+            waiting_binding_index: None,
+        }
+    }
+}
+
+impl State for SettingsState {
+    fn update(&mut self, context: &mut GameContext, egui_ctx: &egui::CtxRef) -> Transition {
+        let mut transition = Transition::None;
+        let old_settings_data = context.settings_data.clone();
+        if !settings_menu::capture_binding(
+            &mut context.settings_data,
+            &mut self.waiting_binding_index,
+        ) && is_key_pressed(KeyCode::Escape)
+        {
+            transition = Transition::Pop;
+        }
+
+        egui::Window::new("Settings").show(egui_ctx, |ui| {
+            if settings_menu::show_settings(
+                ui,
+                &mut context.settings_data,
+                &context.game_data,
+                &context.renderers,
+                context.renderer_index,
+                &mut self.waiting_binding_index,
+            ) {
+                transition = Transition::Pop;
+            }
+        });
+
+        context.apply_changed_settings(&old_settings_data);
+        transition
+    }
+
+    fn on_exit(&mut self, context: &mut GameContext) {
+        context.save_settings();
+    }
+}
@@ -0,0 +1,452 @@
+use macroquad::prelude::*;
+use parry2d::math::Isometry;
+use parry2d::na::{Point2, Vector2};
+use parry2d::query::details::closest_points_segment_segment_with_locations;
+use parry2d::query::{self, Ray, RayCast};
+use parry2d::shape::{Ball, Capsule, Segment, SegmentPointLocation};
+
+use crate::game_data::{
+    ConnectionData, CrossingPolicy, LayoutAdditionalData, LevelAdditionalData, LevelData, PointType,
+};
+use crate::replay::ReplayActionType;
+use crate::rules::{self, RejectionReason};
+use crate::spatial_grid::{get_segment_bounds, ConnectionsGrid};
+
+// the logic of GameState::Level that does not depend on the window,
+// shared by the game and the headless replay verifier
+
+pub const POINT_RADIUS: f32 = 0.25;
+// closer than this, in layout cells, shapes touch
+const CONTACT_DISTANCE: f32 = 0.0001;
+
+fn is_under(position: Vec2, point_position: Vec2) -> bool {
+    position.distance_squared(point_position) < POINT_RADIUS * POINT_RADIUS
+}
+
+// the point of the current layout the next connection starts from,
+// None when the level is finished or the layout has no start
+pub fn get_current_start_index(
+    level_additional_data: &LevelAdditionalData,
+    connections_data: &[ConnectionData],
+    layout_index: usize,
+) -> Option<usize> {
+    if let Some(connection_data) = connections_data.last() {
+        rules::get_next_start(level_additional_data, connection_data)
+            .map(|point_id| point_id.point_index)
+    } else {
+        // the level may be opened in another layout (see --layout), there is nothing to start from
+        level_additional_data.layouts_data[layout_index].start_point_index
+    }
+}
+
+// the position of a free point within snap_radius of the position, the position itself otherwise
+pub fn snap_target_position(
+    level_additional_data: &LevelAdditionalData,
+    connections_data: &[ConnectionData],
+    layout_index: usize,
+    current_start_index: usize,
+    position: Vec2,
+    snap_radius: f32,
+) -> Vec2 {
+    let layout_data = &level_additional_data.layouts_data[layout_index];
+    let radius = vec2(snap_radius, snap_radius);
+    let mut target_position = position;
+    for i in layout_data
+        .points_grid
+        .get_indices(position - radius, position + radius)
+    {
+        let point_data = &layout_data.points_data[i];
+        if i != current_start_index
+            && position.distance_squared(point_data.position) < snap_radius * snap_radius
+            && !rules::is_point_used(level_additional_data, connections_data, layout_index, i)
+        {
+            target_position = point_data.position;
+        }
+    }
+    target_position
+}
+
+// the first point where the segment from -> to is blocked by a connection or an obstacle
+pub fn find_intersection(
+    level_data: &LevelData,
+    layout_data: &LayoutAdditionalData,
+    connections_data: &[ConnectionData],
+    connections_grid: &ConnectionsGrid,
+    layout_index: usize,
+    from_position: Vec2,
+    to_position: Vec2,
+) -> Option<Vec2> {
+    find_blocking(
+        level_data,
+        layout_data,
+        connections_data,
+        connections_grid,
+        layout_index,
+        from_position,
+        to_position,
+    )
+    .map(|(position, _)| position)
+}
+
+// whether two touching segments meet only at an end of one of them
+fn is_touching_at_end(segment: &Segment, another_segment: &Segment) -> bool {
+    let direction = segment.b - segment.a;
+    let another_direction = another_segment.b - another_segment.a;
+    let length = direction.norm();
+    let another_length = another_direction.norm();
+    if length < CONTACT_DISTANCE || another_length < CONTACT_DISTANCE {
+        return true;
+    }
+    // parallel segments touch along their common part, it has to be a single point
+    if direction.perp(&another_direction).abs() < CONTACT_DISTANCE * length * another_length {
+        let axis = direction / length;
+        let a = (another_segment.a - segment.a).dot(&axis);
+        let b = (another_segment.b - segment.a).dot(&axis);
+        let overlap = a.max(b).min(length) - a.min(b).max(0.0);
+        return overlap < CONTACT_DISTANCE;
+    }
+    let (location, another_location) = closest_points_segment_segment_with_locations(
+        &Isometry::identity(),
+        segment,
+        another_segment,
+    );
+    let is_at_end = |location: SegmentPointLocation, length: f32| {
+        let [u, v] = location.barycentric_coordinates();
+        u.min(v) * length < CONTACT_DISTANCE
+    };
+    is_at_end(location, length) || is_at_end(another_location, another_length)
+}
+
+// the first hit and what was hit, the level crossing policy and clearance decide what blocks,
+// connections_grid has to be updated with connections_data
+pub fn find_blocking(
+    level_data: &LevelData,
+    layout_data: &LayoutAdditionalData,
+    connections_data: &[ConnectionData],
+    connections_grid: &ConnectionsGrid,
+    layout_index: usize,
+    from_position: Vec2,
+    to_position: Vec2,
+) -> Option<(Vec2, RejectionReason)> {
+    let vector = to_position - from_position;
+    let ray = Ray::new(
+        Point2::new(from_position.x, from_position.y),
+        Vector2::new(vector.x, vector.y),
+    );
+    let segment = Segment::new(
+        Point2::new(from_position.x, from_position.y),
+        Point2::new(to_position.x, to_position.y),
+    );
+    let min_clearance = level_data.min_clearance;
+    // only the things within the bounds of the segment and the clearance around it may block
+    let (min, max) = get_segment_bounds(&segment);
+    let margin = vec2(1.0, 1.0) * (min_clearance + CONTACT_DISTANCE);
+    let (min, max) = (min - margin, max + margin);
+    let mut blocking = None;
+    let mut min_time: f32 = 1.0;
+    let mut update_blocking = |time: Option<f32>, reason| {
+        // the shape is within reach but the ray may miss it by a rounding error
+        let time = time.unwrap_or(1.0);
+        if blocking.is_none() || time < min_time {
+            blocking = Some(reason);
+            min_time = time;
+        }
+    };
+
+    if level_data.crossing_policy != CrossingPolicy::AllowSelfCrossing {
+        for i in connections_grid.get_indices(layout_index, min, max) {
+            let another_segment = &connections_data[i].segment;
+            let distance = query::distance(
+                &Isometry::identity(),
+                &segment,
+                &Isometry::identity(),
+                another_segment,
+            )
+            .unwrap_or(0.0);
+            let reason = if distance < CONTACT_DISTANCE {
+                if level_data.crossing_policy == CrossingPolicy::AllowEndpointTouching
+                    && is_touching_at_end(&segment, another_segment)
+                {
+                    continue;
+                }
+                RejectionReason::BlockedByConnection
+            } else if distance < min_clearance {
+                RejectionReason::TooClose
+            } else {
+                continue;
+            };
+            let time = if 0.0 < min_clearance {
+                Capsule::new(another_segment.a, another_segment.b, min_clearance).cast_ray(
+                    &Isometry::identity(),
+                    &ray,
+                    1.0,
+                    true,
+                )
+            } else {
+                another_segment.cast_ray(&Isometry::identity(), &ray, 1.0, true)
+            };
+            update_blocking(time, reason);
+        }
+    }
+
+    for i in layout_data.obstacles_grid.get_indices(min, max) {
+        let obstacle_data = &layout_data.obstacles_data[i];
+        let isometry = obstacle_data.get_isometry();
+        let distance = query::distance(
+            &Isometry::identity(),
+            &segment,
+            &isometry,
+            &obstacle_data.ball,
+        )
+        .unwrap_or(0.0);
+        let reason = if distance < CONTACT_DISTANCE {
+            RejectionReason::BlockedByObstacle
+        } else if distance < min_clearance {
+            RejectionReason::TooClose
+        } else {
+            continue;
+        };
+        let time =
+            Ball::new(obstacle_data.radius + min_clearance).cast_ray(&isometry, &ray, 1.0, true);
+        update_blocking(time, reason);
+    }
+
+    blocking.map(|reason| (from_position + vector * min_time, reason))
+}
+
+// index of the connection to cut the path at when the player presses at position
+pub fn find_undo_index(
+    level_additional_data: &LevelAdditionalData,
+    connections_data: &[ConnectionData],
+    layout_index: usize,
+    position: Vec2,
+) -> Option<usize> {
+    for (i, connection_data) in connections_data.iter().enumerate() {
+        if connection_data.layout_index == layout_index {
+            let point_data = &level_additional_data.layouts_data[connection_data.layout_index]
+                .points_data[connection_data.from_point_index];
+            if is_under(position, point_data.position) {
+                return Some(i);
+            }
+        }
+
+        let point_data = &level_additional_data.layouts_data[connection_data.layout_index]
+            .points_data[connection_data.to_point_index];
+        if let PointType::Common {
+            layout_index: pair_layout_index,
+            pair_index,
+        } = point_data.point_type
+        {
+            if pair_layout_index == layout_index {
+                let point_data =
+                    &level_additional_data.layouts_data[pair_layout_index].points_data[pair_index];
+                if is_under(position, point_data.position) {
+                    return Some(i);
+                }
+            }
+        }
+    }
+    None
+}
+
+// the point a connection from current_start_index goes to when the player releases at position
+pub fn find_connection_target(
+    level_data: &LevelData,
+    level_additional_data: &LevelAdditionalData,
+    connections_data: &[ConnectionData],
+    layout_index: usize,
+    current_start_index: usize,
+    position: Vec2,
+) -> Result<usize, RejectionReason> {
+    let layout_data = &level_additional_data.layouts_data[layout_index];
+    let radius = vec2(POINT_RADIUS, POINT_RADIUS);
+    let point_index = layout_data
+        .points_grid
+        .get_indices(position - radius, position + radius)
+        .into_iter()
+        .find(|&i| {
+            i != current_start_index && is_under(position, layout_data.points_data[i].position)
+        })
+        .ok_or(RejectionReason::NoPoint)?;
+    rules::check_target(
+        level_data,
+        level_additional_data,
+        connections_data,
+        layout_index,
+        point_index,
+    )?;
+    Ok(point_index)
+}
+
+// the target of a connection from current_start_index that nothing blocks on the way
+pub fn try_connection(
+    level_data: &LevelData,
+    level_additional_data: &LevelAdditionalData,
+    connections_data: &[ConnectionData],
+    connections_grid: &ConnectionsGrid,
+    layout_index: usize,
+    current_start_index: usize,
+    position: Vec2,
+) -> Result<usize, RejectionReason> {
+    let to_point_index = find_connection_target(
+        level_data,
+        level_additional_data,
+        connections_data,
+        layout_index,
+        current_start_index,
+        position,
+    )?;
+    let layout_data = &level_additional_data.layouts_data[layout_index];
+    let from_position = layout_data.points_data[current_start_index].position;
+    // the connection goes to the center of the point, not to the released position
+    let to_position = layout_data.points_data[to_point_index].position;
+    match find_blocking(
+        level_data,
+        layout_data,
+        connections_data,
+        connections_grid,
+        layout_index,
+        from_position,
+        to_position,
+    ) {
+        Some((_, reason)) => Err(reason),
+        None => Ok(to_point_index),
+    }
+}
+
+pub fn is_win(
+    level_additional_data: &LevelAdditionalData,
+    connections_data: &[ConnectionData],
+) -> bool {
+    if let Some(connection_data) = connections_data.last() {
+        let last_point = &level_additional_data.layouts_data[connection_data.layout_index]
+            .points_data[connection_data.to_point_index];
+        last_point.point_type == PointType::Finish
+    } else {
+        false
+    }
+}
+
+// replays a recorded action through the same checks the player input goes through,
+// returns false (and leaves connections untouched) if the game would not accept it,
+// connections_grid is brought up to date with connections_data first
+pub fn simulate_action(
+    level_data: &LevelData,
+    level_additional_data: &LevelAdditionalData,
+    connections_data: &mut Vec<ConnectionData>,
+    connections_grid: &mut ConnectionsGrid,
+    action_type: &ReplayActionType,
+) -> bool {
+    connections_grid.update(connections_data);
+    let layout_index = level_additional_data.get_current_layout_index(connections_data);
+    let layout_data = &level_additional_data.layouts_data[layout_index];
+    let current_start_index =
+        match get_current_start_index(level_additional_data, connections_data, layout_index) {
+            Some(current_start_index) => current_start_index,
+            None => return false,
+        };
+
+    match *action_type {
+        ReplayActionType::Connect {
+            layout_index: action_layout_index,
+            from_point_index,
+            to_point_index,
+        } => {
+            if action_layout_index != layout_index
+                || from_point_index != current_start_index
+                || layout_data.points_data.len() <= to_point_index
+            {
+                return false;
+            }
+            let position = snap_target_position(
+                level_additional_data,
+                connections_data,
+                layout_index,
+                current_start_index,
+                layout_data.points_data[to_point_index].position,
+                POINT_RADIUS,
+            );
+            if try_connection(
+                level_data,
+                level_additional_data,
+                connections_data,
+                connections_grid,
+                layout_index,
+                current_start_index,
+                position,
+            ) != Ok(to_point_index)
+            {
+                return false;
+            }
+            connections_data.push(ConnectionData::new(
+                layout_data,
+                layout_index,
+                from_point_index,
+                to_point_index,
+            ));
+            true
+        }
+        ReplayActionType::Undo { connection_index } => {
+            let connection_data = match connections_data.get(connection_index) {
+                Some(connection_data) => connection_data,
+                None => return false,
+            };
+            // the player can press either the start of the connection or the portal it came from
+            let mut positions = Vec::new();
+            if connection_data.layout_index == layout_index {
+                positions.push(layout_data.points_data[connection_data.from_point_index].position);
+            }
+            let to_point_data = &level_additional_data.layouts_data[connection_data.layout_index]
+                .points_data[connection_data.to_point_index];
+            if let PointType::Common {
+                layout_index: pair_layout_index,
+                pair_index,
+            } = to_point_data.point_type
+            {
+                if pair_layout_index == layout_index {
+                    positions.push(layout_data.points_data[pair_index].position);
+                }
+            }
+            let is_reachable = positions.into_iter().any(|position| {
+                find_undo_index(
+                    level_additional_data,
+                    connections_data,
+                    layout_index,
+                    position,
+                ) == Some(connection_index)
+            });
+            if is_reachable {
+                connections_data.truncate(connection_index);
+            }
+            is_reachable
+        }
+    }
+}
+
+// keeps the connections that are still possible after the level was changed,
+// up to the first one that is not
+pub fn restore_connections(
+    level_data: &LevelData,
+    level_additional_data: &LevelAdditionalData,
+    old_connections_data: &[ConnectionData],
+) -> Vec<ConnectionData> {
+    let mut connections_data = Vec::new();
+    let mut connections_grid = ConnectionsGrid::default();
+    for connection_data in old_connections_data {
+        let action_type = ReplayActionType::Connect {
+            layout_index: connection_data.layout_index,
+            from_point_index: connection_data.from_point_index,
+            to_point_index: connection_data.to_point_index,
+        };
+        if !simulate_action(
+            level_data,
+            level_additional_data,
+            &mut connections_data,
+            &mut connections_grid,
+            &action_type,
+        ) {
+            break;
+        }
+    }
+    connections_data
+}
@@ -0,0 +1,133 @@
+use crate::game_data::{ConnectionData, LevelAdditionalData, LevelData};
+use crate::replay::ReplayActionType;
+use crate::simulation;
+use crate::spatial_grid::ConnectionsGrid;
+
+// what the search found, solutions are the different paths from the start to the finish
+pub struct SolutionsData {
+    pub solutions_count: usize, // stops growing after it exceeds max_solutions
+    pub shortest_solution: Option<Vec<ReplayActionType>>,
+    pub is_complete: bool, // every path was checked, false if max_steps ran out
+}
+
+impl SolutionsData {
+    // the level has a solution and no more of them than the search was asked for
+    pub fn is_bounded(&self, max_solutions: usize) -> bool {
+        self.is_complete && 0 < self.solutions_count && self.solutions_count <= max_solutions
+    }
+}
+
+struct Solver<'a> {
+    level_data: &'a LevelData,
+    level_additional_data: &'a LevelAdditionalData,
+    max_solutions: usize,
+    steps_left: usize,
+    connections_data: Vec<ConnectionData>,
+    connections_grid: ConnectionsGrid,
+    solutions_data: SolutionsData,
+}
+
+impl<'a> Solver<'a> {
+    fn is_stopped(&self) -> bool {
+        self.max_solutions < self.solutions_data.solutions_count || !self.solutions_data.is_complete
+    }
+
+    fn search(&mut self) {
+        if self.steps_left == 0 {
+            self.solutions_data.is_complete = false;
+            return;
+        }
+        self.steps_left -= 1;
+
+        let layout_index = self
+            .level_additional_data
+            .get_current_layout_index(&self.connections_data);
+        let current_start_index = match simulation::get_current_start_index(
+            self.level_additional_data,
+            &self.connections_data,
+            layout_index,
+        ) {
+            Some(current_start_index) => current_start_index,
+            None => return,
+        };
+        let layout_data = &self.level_additional_data.layouts_data[layout_index];
+        for (to_point_index, point_data) in layout_data.points_data.iter().enumerate() {
+            if to_point_index == current_start_index {
+                continue;
+            }
+            // the same check as a release of the mouse right over the point
+            if simulation::try_connection(
+                self.level_data,
+                self.level_additional_data,
+                &self.connections_data,
+                &self.connections_grid,
+                layout_index,
+                current_start_index,
+                point_data.position,
+            ) != Ok(to_point_index)
+            {
+                continue;
+            }
+            self.connections_data.push(ConnectionData::new(
+                layout_data,
+                layout_index,
+                current_start_index,
+                to_point_index,
+            ));
+            self.connections_grid.update(&self.connections_data);
+
+            if simulation::is_win(self.level_additional_data, &self.connections_data) {
+                self.add_solution();
+            } else {
+                self.search();
+            }
+
+            self.connections_data.pop();
+            self.connections_grid.update(&self.connections_data);
+            if self.is_stopped() {
+                return;
+            }
+        }
+    }
+
+    fn add_solution(&mut self) {
+        self.solutions_data.solutions_count += 1;
+        let is_shortest = match &self.solutions_data.shortest_solution {
+            Some(solution) => self.connections_data.len() < solution.len(),
+            None => true,
+        };
+        if is_shortest {
+            let solution = self
+                .connections_data
+                .iter()
+                .map(|connection_data| ReplayActionType::Connect {
+                    layout_index: connection_data.layout_index,
+                    from_point_index: connection_data.from_point_index,
+                    to_point_index: connection_data.to_point_index,
+                })
+                .collect();
+            self.solutions_data.shortest_solution = Some(solution);
+        }
+    }
+}
+
+// counts the solutions of the level up to max_solutions + 1,
+// max_steps limits the number of the paths tried
+pub fn solve(level_data: &LevelData, max_solutions: usize, max_steps: usize) -> SolutionsData {
+    let level_additional_data = LevelAdditionalData::new(level_data);
+    let mut solver = Solver {
+        level_data,
+        level_additional_data: &level_additional_data,
+        max_solutions,
+        steps_left: max_steps,
+        connections_data: Vec::new(),
+        connections_grid: ConnectionsGrid::default(),
+        solutions_data: SolutionsData {
+            solutions_count: 0,
+            shortest_solution: None,
+            is_complete: true,
+        },
+    };
+    solver.search();
+    solver.solutions_data
+}
@@ -0,0 +1,118 @@
+use std::collections::HashMap;
+
+use macroquad::prelude::*;
+use parry2d::shape::Segment;
+
+use crate::game_data::ConnectionData;
+
+// in layout cells
+pub const GRID_CELL_SIZE: f32 = 4.0;
+
+// the broad phase of the point and collision queries on a layout:
+// a uniform grid that gives the things whose bounds share grid cells with the bounds asked
+#[derive(Default)]
+pub struct SpatialGrid {
+    cells: HashMap<(i32, i32), Vec<usize>>,
+}
+
+fn get_cell(position: Vec2) -> (i32, i32) {
+    (
+        (position.x / GRID_CELL_SIZE).floor() as i32,
+        (position.y / GRID_CELL_SIZE).floor() as i32,
+    )
+}
+
+fn get_cells(min: Vec2, max: Vec2) -> impl Iterator<Item = (i32, i32)> {
+    let (min_x, min_y) = get_cell(min);
+    let (max_x, max_y) = get_cell(max);
+    (min_y..=max_y).flat_map(move |y| (min_x..=max_x).map(move |x| (x, y)))
+}
+
+pub fn get_segment_bounds(segment: &Segment) -> (Vec2, Vec2) {
+    (
+        vec2(segment.a.x.min(segment.b.x), segment.a.y.min(segment.b.y)),
+        vec2(segment.a.x.max(segment.b.x), segment.a.y.max(segment.b.y)),
+    )
+}
+
+impl SpatialGrid {
+    pub fn insert(&mut self, index: usize, min: Vec2, max: Vec2) {
+        for cell in get_cells(min, max) {
+            self.cells.entry(cell).or_default().push(index);
+        }
+    }
+
+    // min and max have to be the ones the index was inserted with
+    pub fn remove(&mut self, index: usize, min: Vec2, max: Vec2) {
+        for cell in get_cells(min, max) {
+            if let Some(indices) = self.cells.get_mut(&cell) {
+                indices.retain(|&i| i != index);
+                if indices.is_empty() {
+                    self.cells.remove(&cell);
+                }
+            }
+        }
+    }
+
+    // in ascending order, so the callers see the things in the order they were indexed
+    pub fn get_indices(&self, min: Vec2, max: Vec2) -> Vec<usize> {
+        let mut indices = Vec::new();
+        for cell in get_cells(min, max) {
+            if let Some(cell_indices) = self.cells.get(&cell) {
+                indices.extend_from_slice(cell_indices);
+            }
+        }
+        indices.sort_unstable();
+        indices.dedup();
+        indices
+    }
+}
+
+// the connections of the path in the grids of their layouts
+#[derive(Default)]
+pub struct ConnectionsGrid {
+    grids: Vec<SpatialGrid>,         // by layout index
+    segments: Vec<(usize, Segment)>, // the layout index and the segment of each indexed connection
+}
+
+impl ConnectionsGrid {
+    pub fn new(connections_data: &[ConnectionData]) -> ConnectionsGrid {
+        let mut connections_grid = ConnectionsGrid::default();
+        connections_grid.update(connections_data);
+        connections_grid
+    }
+
+    // the path grows and shrinks at its end, only the connections after the common part are moved
+    pub fn update(&mut self, connections_data: &[ConnectionData]) {
+        let common_count = self
+            .segments
+            .iter()
+            .zip(connections_data)
+            .take_while(|((layout_index, segment), connection_data)| {
+                *layout_index == connection_data.layout_index && *segment == connection_data.segment
+            })
+            .count();
+        for (index, (layout_index, segment)) in self.segments.drain(common_count..).enumerate() {
+            let (min, max) = get_segment_bounds(&segment);
+            self.grids[layout_index].remove(common_count + index, min, max);
+        }
+        for (index, connection_data) in connections_data.iter().enumerate().skip(common_count) {
+            let layout_index = connection_data.layout_index;
+            if self.grids.len() <= layout_index {
+                self.grids
+                    .resize_with(layout_index + 1, SpatialGrid::default);
+            }
+            let (min, max) = get_segment_bounds(&connection_data.segment);
+            self.grids[layout_index].insert(index, min, max);
+            self.segments.push((layout_index, connection_data.segment));
+        }
+    }
+
+    // indices of the connections in the layout that may be within the bounds
+    pub fn get_indices(&self, layout_index: usize, min: Vec2, max: Vec2) -> Vec<usize> {
+        match self.grids.get(layout_index) {
+            Some(grid) => grid.get_indices(min, max),
+            None => Vec::new(),
+        }
+    }
+}
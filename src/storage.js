@@ -0,0 +1,27 @@
+// the local storage functions of src/storage.rs, needs sapp_jsutils from the bundle
+register_storage_plugin = function (importObject) {
+    importObject.env.storage_get = function (key) {
+        var value = null;
+        try {
+            value = window.localStorage.getItem(get_js_object(key));
+        } catch (error) {
+            console.log(error);
+        }
+        return value === null ? -1 : js_object(value);
+    }
+    importObject.env.storage_set = function (key, value) {
+        try {
+            window.localStorage.setItem(get_js_object(key), get_js_object(value));
+            return 1;
+        } catch (error) {
+            console.log(error);
+            return 0;
+        }
+    }
+}
+
+miniquad_add_plugin({
+    register_plugin: register_storage_plugin,
+    name: "storage",
+    version: "0.1.0"
+});
@@ -0,0 +1,51 @@
+// the files of the player, the settings and the progress:
+// next to the game on desktop, in the local storage of the browser at the WebGL version,
+// src/storage.js provides the local storage functions
+
+#[cfg(target_arch = "wasm32")]
+use sapp_jsutils::{JsObject, JsObjectWeak};
+
+#[cfg(target_arch = "wasm32")]
+extern "C" {
+    fn storage_get(key: JsObjectWeak) -> JsObject;
+    fn storage_set(key: JsObjectWeak, value: JsObjectWeak) -> u32;
+}
+
+// None if the file was never written
+#[cfg(not(target_arch = "wasm32"))]
+pub fn read(file_name: &str) -> Option<Vec<u8>> {
+    std::fs::read(file_name).ok()
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn read(file_name: &str) -> Option<Vec<u8>> {
+    let key = JsObject::string(file_name);
+    let value = unsafe { storage_get(key.weak()) };
+    if value.is_nil() {
+        return None;
+    }
+    let mut text = String::new();
+    value.to_string(&mut text);
+    Some(text.into_bytes())
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn write(file_name: &str, text: &str) -> Result<(), String> {
+    std::fs::write(file_name, text)
+        .map_err(|error| format!("File '{}' not saved: {}", file_name, error))
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn write(file_name: &str, text: &str) -> Result<(), String> {
+    let key = JsObject::string(file_name);
+    let value = JsObject::string(text);
+    // the browser may refuse it, when the storage is full or disabled
+    if unsafe { storage_set(key.weak(), value.weak()) } == 1 {
+        Ok(())
+    } else {
+        Err(format!(
+            "File '{}' not saved to the local storage",
+            file_name
+        ))
+    }
+}
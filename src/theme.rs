@@ -0,0 +1,85 @@
+use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
+
+// colors are (r, g, b, a) in 0..1, texture paths are relative to the theme file
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ThemeData {
+    pub title: String,
+    pub background_color: [f32; 4],
+    pub background_texture: Option<String>,
+    pub grid_color: [f32; 4],
+    pub border_color: [f32; 4],
+    pub current_layout_color: [f32; 4],
+    pub point_color: [f32; 4],
+    // colors of pairs by their digit, wrapped around when there are more pairs
+    pub pair_colors: Vec<[f32; 4]>,
+    pub pair_label_color: [f32; 4],
+    pub start_color: [f32; 4],
+    pub finish_color: [f32; 4],
+    pub obstacle_color: [f32; 4],
+    pub connection_color: [f32; 4],
+    pub aim_color: [f32; 4],
+    pub blocked_aim_color: [f32; 4],
+    pub pair_link_color: [f32; 4],
+    pub highlight_color: [f32; 4],
+    pub line_thickness: f32,
+    pub grid_dot_radius: f32,
+    pub point_texture: Option<String>,
+    pub start_texture: Option<String>,
+    pub finish_texture: Option<String>,
+    pub obstacle_texture: Option<String>,
+}
+
+// the colors the game was drawn with before themes
+impl Default for ThemeData {
+    fn default() -> Self {
+        ThemeData {
+            title: "Default".to_string(),
+            background_color: BLACK.into(),
+            background_texture: None,
+            grid_color: DARKGRAY.into(),
+            border_color: GRAY.into(),
+            current_layout_color: WHITE.into(),
+            point_color: ORANGE.into(),
+            pair_colors: vec![
+                ORANGE.into(),
+                SKYBLUE.into(),
+                PINK.into(),
+                LIME.into(),
+                PURPLE.into(),
+                BEIGE.into(),
+            ],
+            pair_label_color: BLACK.into(),
+            start_color: YELLOW.into(),
+            finish_color: GREEN.into(),
+            obstacle_color: BLUE.into(),
+            connection_color: VIOLET.into(),
+            aim_color: RED.into(),
+            blocked_aim_color: DARKBLUE.into(),
+            pair_link_color: [1.0, 0.63, 0.0, 0.25],
+            highlight_color: WHITE.into(),
+            line_thickness: 0.1,
+            grid_dot_radius: 0.1,
+            point_texture: None,
+            start_texture: None,
+            finish_texture: None,
+            obstacle_texture: None,
+        }
+    }
+}
+
+impl ThemeData {
+    pub fn from_bytes(bytes: &[u8]) -> Result<ThemeData, String> {
+        ron::de::from_bytes(bytes).map_err(|error| format!("Theme parsing failed: {}", error))
+    }
+
+    pub fn get_pair_color(&self, pair_id: Option<char>) -> [f32; 4] {
+        match pair_id.and_then(|pair_id| pair_id.to_digit(10)) {
+            Some(digit) if !self.pair_colors.is_empty() => {
+                self.pair_colors[digit as usize % self.pair_colors.len()]
+            }
+            _ => self.point_color,
+        }
+    }
+}
@@ -0,0 +1,78 @@
+use macroquad::prelude::*;
+
+use gmtk_jam_2021::rules::RejectionReason;
+
+pub const TOAST_DURATION: f64 = 1.5;
+pub const MESSAGE_TOAST_DURATION: f64 = 4.0; // for the results of the buttons, longer to read
+pub const TOAST_CURSOR_OFFSET: f32 = 16.0; // in egui points
+
+// a short message next to the mouse cursor, why a connection was not made or what a button did
+pub struct Toast {
+    pub text: String,
+    pub position: egui::Pos2,
+    pub start_time: f64,
+    pub duration: f64,
+}
+
+impl Toast {
+    pub fn new(text: String, duration: f64, egui_ctx: &egui::CtxRef) -> Toast {
+        Toast {
+            text,
+            position: get_toast_position(egui_ctx),
+            start_time: get_time(),
+            duration,
+        }
+    }
+
+    pub fn is_shown(&self, time: f64) -> bool {
+        time - self.start_time < self.duration
+    }
+
+    pub fn show(&self, egui_ctx: &egui::CtxRef) {
+        egui::show_tooltip_at(
+            egui_ctx,
+            egui::Id::new("toast"),
+            Some(self.position),
+            |ui| {
+                ui.label(&self.text);
+            },
+        );
+    }
+}
+
+// None when there is nothing to explain
+pub fn get_rejection_description(rejection_reason: RejectionReason) -> Option<String> {
+    let description = match rejection_reason {
+        RejectionReason::NoPoint => return None,
+        RejectionReason::PointUsed => "This point is already used".to_string(),
+        RejectionReason::BlockedByConnection => "The way is crossed by a connection".to_string(),
+        RejectionReason::BlockedByObstacle => "The way is blocked by an obstacle".to_string(),
+        RejectionReason::TooClose => "The way passes too close to something".to_string(),
+        RejectionReason::NotEnoughConnections {
+            connections_count,
+            win_count,
+        } => format!(
+            "Not enough connections for the finish: {}/{}",
+            connections_count, win_count
+        ),
+        RejectionReason::TooManyConnections {
+            connections_count,
+            win_count,
+        } => format!(
+            "The finish takes exactly {} connections, there are {}",
+            win_count, connections_count
+        ),
+        RejectionReason::OnlyFinishLeft => "Only the finish is left to connect".to_string(),
+        RejectionReason::OutOfMoves => "No moves left, undo a connection".to_string(),
+    };
+    Some(description)
+}
+
+fn get_toast_position(egui_ctx: &egui::CtxRef) -> egui::Pos2 {
+    let mouse_position = mouse_position();
+    let pixels_per_point = egui_ctx.pixels_per_point();
+    egui::pos2(
+        mouse_position.0 / pixels_per_point + TOAST_CURSOR_OFFSET,
+        mouse_position.1 / pixels_per_point + TOAST_CURSOR_OFFSET,
+    )
+}
@@ -0,0 +1,38 @@
+// every test file uses only some of the helpers
+#![allow(dead_code)]
+
+use gmtk_jam_2021::game_data::{ConnectionData, LevelAdditionalData, LevelData};
+
+pub fn make_level(win_count: usize, layouts: &[&str]) -> LevelData {
+    LevelData {
+        name: "Test".to_string(),
+        win_count,
+        layouts: layouts.iter().map(|layout| layout.to_string()).collect(),
+        ..LevelData::default()
+    }
+}
+
+// layout 0:    layout 1:
+// s0.          1.0
+// f.1
+// points of layout 0: 0 - '0', 1 - '1', 2 - start, 3 - finish
+// points of layout 1: 0 - '1', 1 - '0'
+// only s -> 0, 1 -> 1, 1 -> f solves it, the other way crosses itself before the finish
+pub fn make_portal_level() -> LevelData {
+    make_level(2, &["s0. f.1", "1.0"])
+}
+
+pub fn connect(
+    level_additional_data: &LevelAdditionalData,
+    connections_data: &mut Vec<ConnectionData>,
+    layout_index: usize,
+    from_point_index: usize,
+    to_point_index: usize,
+) {
+    connections_data.push(ConnectionData::new(
+        &level_additional_data.layouts_data[layout_index],
+        layout_index,
+        from_point_index,
+        to_point_index,
+    ));
+}
@@ -0,0 +1,341 @@
+mod common;
+
+use gmtk_jam_2021::game_data::{ConnectionData, CrossingPolicy, LevelAdditionalData, LevelData};
+use gmtk_jam_2021::rules::RejectionReason;
+use gmtk_jam_2021::simulation;
+use gmtk_jam_2021::spatial_grid::ConnectionsGrid;
+use macroquad::prelude::*;
+
+// 0...1
+// .....
+// ..z..
+// .....
+// 2...3
+// the connection 0 -> 1 goes along the top, the obstacle is in the middle
+const LAYOUTS: &[&str] = &["0...1 ..... ..z.. ..... 2...3"];
+
+fn find_blocking(
+    level_data: &LevelData,
+    layout_index: usize,
+    from_position: Vec2,
+    to_position: Vec2,
+) -> Option<(Vec2, RejectionReason)> {
+    let level_additional_data = LevelAdditionalData::new(level_data);
+    let layout_data = &level_additional_data.layouts_data[0];
+    let connections_data = vec![ConnectionData::new(layout_data, 0, 0, 1)];
+    simulation::find_blocking(
+        level_data,
+        layout_data,
+        &connections_data,
+        &ConnectionsGrid::new(&connections_data),
+        layout_index,
+        from_position,
+        to_position,
+    )
+}
+
+fn get_reason(
+    crossing_policy: CrossingPolicy,
+    from_position: Vec2,
+    to_position: Vec2,
+) -> Option<RejectionReason> {
+    find_blocking(
+        &LevelData {
+            crossing_policy,
+            ..common::make_level(1, LAYOUTS)
+        },
+        0,
+        from_position,
+        to_position,
+    )
+    .map(|(_, reason)| reason)
+}
+
+#[test]
+fn crossing() {
+    let from_position = vec2(2.0, -1.0);
+    let to_position = vec2(2.0, 1.0);
+    let blocking = find_blocking(
+        &common::make_level(1, LAYOUTS),
+        0,
+        from_position,
+        to_position,
+    );
+    let (position, reason) = blocking.unwrap();
+    assert_eq!(reason, RejectionReason::BlockedByConnection);
+    assert!(position.distance(vec2(2.0, 0.0)) < 0.001);
+    assert_eq!(
+        get_reason(
+            CrossingPolicy::AllowEndpointTouching,
+            from_position,
+            to_position
+        ),
+        Some(RejectionReason::BlockedByConnection)
+    );
+    assert_eq!(
+        get_reason(
+            CrossingPolicy::AllowSelfCrossing,
+            from_position,
+            to_position
+        ),
+        None
+    );
+}
+
+#[test]
+fn touching_at_end() {
+    let touchings = [
+        // ends on the connection
+        (vec2(2.0, 1.0), vec2(2.0, 0.0)),
+        // starts on the connection
+        (vec2(2.0, 0.0), vec2(2.0, 1.0)),
+        // goes through the end of the connection
+        (vec2(0.0, -1.0), vec2(0.0, 1.0)),
+        // goes on from the end of the connection
+        (vec2(4.0, 0.0), vec2(6.0, 0.0)),
+    ];
+    for &(from_position, to_position) in touchings.iter() {
+        assert_eq!(
+            get_reason(CrossingPolicy::Strict, from_position, to_position),
+            Some(RejectionReason::BlockedByConnection)
+        );
+        assert_eq!(
+            get_reason(
+                CrossingPolicy::AllowEndpointTouching,
+                from_position,
+                to_position
+            ),
+            None
+        );
+    }
+}
+
+#[test]
+fn overlapping() {
+    assert_eq!(
+        get_reason(
+            CrossingPolicy::AllowEndpointTouching,
+            vec2(3.0, 0.0),
+            vec2(6.0, 0.0)
+        ),
+        Some(RejectionReason::BlockedByConnection)
+    );
+    assert_eq!(
+        get_reason(
+            CrossingPolicy::AllowSelfCrossing,
+            vec2(3.0, 0.0),
+            vec2(6.0, 0.0)
+        ),
+        None
+    );
+}
+
+#[test]
+fn clearance() {
+    let from_position = vec2(1.0, 0.3);
+    let to_position = vec2(3.0, 0.3);
+    assert_eq!(
+        find_blocking(
+            &LevelData {
+                crossing_policy: CrossingPolicy::AllowEndpointTouching,
+                ..common::make_level(1, LAYOUTS)
+            },
+            0,
+            from_position,
+            to_position
+        ),
+        None
+    );
+    assert_eq!(
+        find_blocking(
+            &LevelData {
+                crossing_policy: CrossingPolicy::AllowEndpointTouching,
+                min_clearance: 0.2,
+                ..common::make_level(1, LAYOUTS)
+            },
+            0,
+            from_position,
+            to_position
+        ),
+        None
+    );
+    let (position, reason) = find_blocking(
+        &LevelData {
+            crossing_policy: CrossingPolicy::AllowEndpointTouching,
+            min_clearance: 0.5,
+            ..common::make_level(1, LAYOUTS)
+        },
+        0,
+        from_position,
+        to_position,
+    )
+    .unwrap();
+    assert_eq!(reason, RejectionReason::TooClose);
+    assert!(position.distance(from_position) < 0.001);
+    // the path may cross itself but not come close to obstacles
+    assert_eq!(
+        find_blocking(
+            &LevelData {
+                crossing_policy: CrossingPolicy::AllowSelfCrossing,
+                min_clearance: 0.5,
+                ..common::make_level(1, LAYOUTS)
+            },
+            0,
+            from_position,
+            to_position
+        ),
+        None
+    );
+}
+
+#[test]
+fn obstacles() {
+    for &crossing_policy in [
+        CrossingPolicy::Strict,
+        CrossingPolicy::AllowEndpointTouching,
+        CrossingPolicy::AllowSelfCrossing,
+    ]
+    .iter()
+    {
+        let (position, reason) = find_blocking(
+            &LevelData {
+                crossing_policy,
+                ..common::make_level(1, LAYOUTS)
+            },
+            0,
+            vec2(0.0, 2.0),
+            vec2(4.0, 2.0),
+        )
+        .unwrap();
+        assert_eq!(reason, RejectionReason::BlockedByObstacle);
+        assert!(position.distance(vec2(1.5, 2.0)) < 0.001);
+    }
+
+    let from_position = vec2(0.0, 2.7);
+    let to_position = vec2(4.0, 2.7);
+    assert_eq!(
+        find_blocking(
+            &LevelData {
+                min_clearance: 0.1,
+                ..common::make_level(1, LAYOUTS)
+            },
+            0,
+            from_position,
+            to_position
+        ),
+        None
+    );
+    let (position, reason) = find_blocking(
+        &LevelData {
+            min_clearance: 0.3,
+            ..common::make_level(1, LAYOUTS)
+        },
+        0,
+        from_position,
+        to_position,
+    )
+    .unwrap();
+    assert_eq!(reason, RejectionReason::TooClose);
+    assert!(position.x < 2.0);
+}
+
+#[test]
+fn other_layout() {
+    assert_eq!(
+        find_blocking(
+            &LevelData {
+                min_clearance: 0.5,
+                ..common::make_level(1, LAYOUTS)
+            },
+            1,
+            vec2(2.0, -1.0),
+            vec2(2.0, 1.0)
+        ),
+        None
+    );
+}
+
+#[test]
+fn released_off_center() {
+    // the connection goes to the center of point 3, closer to the obstacle than the release
+    let level_data = LevelData {
+        min_clearance: 1.55,
+        ..common::make_level(1, LAYOUTS)
+    };
+    let level_additional_data = LevelAdditionalData::new(&level_data);
+    let try_connection = |position| {
+        simulation::try_connection(
+            &level_data,
+            &level_additional_data,
+            &[],
+            &ConnectionsGrid::default(),
+            0,
+            2,
+            position,
+        )
+    };
+    assert_eq!(
+        try_connection(vec2(4.0, 4.2)),
+        Err(RejectionReason::TooClose)
+    );
+    assert_eq!(
+        try_connection(vec2(4.0, 4.0)),
+        Err(RejectionReason::TooClose)
+    );
+    let level_data = LevelData {
+        min_clearance: 1.45,
+        ..common::make_level(1, LAYOUTS)
+    };
+    let level_additional_data = LevelAdditionalData::new(&level_data);
+    assert_eq!(
+        simulation::try_connection(
+            &level_data,
+            &level_additional_data,
+            &[],
+            &ConnectionsGrid::default(),
+            0,
+            2,
+            vec2(4.0, 3.8),
+        ),
+        Ok(3)
+    );
+}
+
+#[test]
+fn ending_on_connection() {
+    // 0.1.2
+    // .....
+    // ..3..
+    // the connection 0 -> 2 goes through point 1, the first version of the game
+    // let 3 -> 1 end on it, Strict rejects any contact
+    let level_data = common::make_level(1, &["0.1.2 ..... ..3.."]);
+    let level_additional_data = LevelAdditionalData::new(&level_data);
+    let connections_data = vec![ConnectionData::new(
+        &level_additional_data.layouts_data[0],
+        0,
+        0,
+        2,
+    )];
+    let try_connection = |level_data| {
+        simulation::try_connection(
+            level_data,
+            &level_additional_data,
+            &connections_data,
+            &ConnectionsGrid::new(&connections_data),
+            0,
+            3,
+            vec2(2.0, 0.0),
+        )
+    };
+    assert_eq!(
+        try_connection(&level_data),
+        Err(RejectionReason::BlockedByConnection)
+    );
+    assert_eq!(
+        try_connection(&LevelData {
+            crossing_policy: CrossingPolicy::AllowEndpointTouching,
+            ..level_data.clone()
+        }),
+        Ok(1)
+    );
+}
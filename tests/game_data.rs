@@ -0,0 +1,77 @@
+use gmtk_jam_2021::game_data::{GameData, ScalingPolicy, DEFAULT_CHAPTER_TITLE};
+
+#[test]
+fn shipped_data_is_valid() {
+    let game_data = GameData::load_from_file_blocking("assets/game.data").unwrap();
+    assert!(game_data.errors.is_empty(), "{:?}", game_data.errors);
+    assert!(game_data.warnings.is_empty(), "{:?}", game_data.warnings);
+    assert!(!game_data.chapters.is_empty());
+    // the virtual resolution is used only by the policies that keep its aspect ratio
+    assert!(game_data.screen.scaling_policy == ScalingPolicy::Letterbox);
+}
+
+#[test]
+fn too_few_moves() {
+    let game_data = GameData::load_from_file_blocking("tests/data/game.data").unwrap();
+    assert_eq!(game_data.levels.len(), 2);
+    // the data stays valid, so a reload with it is not refused
+    assert!(game_data.errors.is_empty(), "{:?}", game_data.errors);
+    assert_eq!(
+        game_data.warnings,
+        vec!["Level 'Too Few Moves' has no solution within 1 moves".to_string()]
+    );
+}
+
+#[test]
+fn manifest() {
+    let game_data = GameData::load_from_file_blocking("tests/data/manifest.data").unwrap();
+    // the levels of the main file come first, then the packs by their order
+    let chapters: Vec<(&str, usize, usize)> = game_data
+        .chapters
+        .iter()
+        .map(|chapter_data| {
+            (
+                chapter_data.title.as_str(),
+                chapter_data.first_level_index,
+                chapter_data.levels_count,
+            )
+        })
+        .collect();
+    assert_eq!(
+        chapters,
+        vec![
+            (DEFAULT_CHAPTER_TITLE, 0, 1),
+            ("Earlier", 1, 1),
+            ("Later", 2, 1),
+            ("Lost", 3, 1),
+        ]
+    );
+    assert_eq!(game_data.get_chapter_index(2), Some(2));
+
+    // the broken file of a pack is an error, the unknown pack to complete is a warning
+    assert_eq!(game_data.errors.len(), 1, "{:?}", game_data.errors);
+    assert!(game_data.errors[0].starts_with("Pack 'tests/data/absent.data' not read"));
+    assert_eq!(
+        game_data.warnings,
+        vec![
+            "Pack 'Lost' waits for the pack 'Missing' that is missing or has no levels".to_string()
+        ]
+    );
+}
+
+#[test]
+fn assets_are_watched() {
+    let game_data = GameData::load_from_file_blocking("assets/game.data").unwrap();
+    // themes, sounds and music are reloaded with the rest of the data
+    for file_name in [
+        "assets/themes/default.theme",
+        "assets/sounds/snap.wav",
+        "assets/music/basics.wav",
+    ] {
+        assert!(
+            game_data.file_names.iter().any(|name| name == file_name),
+            "{:?}",
+            game_data.file_names
+        );
+    }
+}
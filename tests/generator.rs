@@ -0,0 +1,127 @@
+mod common;
+
+use gmtk_jam_2021::game_data::{CrossingPolicy, PackFileData};
+use gmtk_jam_2021::generator::{self, GeneratorData};
+use gmtk_jam_2021::replay::ReplayActionType;
+use gmtk_jam_2021::solver;
+
+#[test]
+fn solve_unique() {
+    let solutions_data = solver::solve(&common::make_portal_level(), 5, 1000);
+    assert!(solutions_data.is_complete);
+    assert_eq!(solutions_data.solutions_count, 1);
+    assert!(solutions_data.is_bounded(1));
+    let solution = solutions_data.shortest_solution.unwrap();
+    assert_eq!(solution.len(), 3);
+    assert!(matches!(
+        solution[0],
+        ReplayActionType::Connect {
+            layout_index: 0,
+            from_point_index: 2,
+            to_point_index: 0,
+        }
+    ));
+}
+
+#[test]
+fn solve_limits() {
+    // the path may cross itself, so both ways solve it
+    let mut level_data = common::make_portal_level();
+    level_data.crossing_policy = CrossingPolicy::AllowSelfCrossing;
+    let solutions_data = solver::solve(&level_data, 5, 1000);
+    assert_eq!(solutions_data.solutions_count, 2);
+    assert!(!solutions_data.is_bounded(1));
+
+    // the search stops at the first solution over the bound
+    assert_eq!(solver::solve(&level_data, 0, 1000).solutions_count, 1);
+
+    let solutions_data = solver::solve(&level_data, 5, 1);
+    assert!(!solutions_data.is_complete);
+    assert!(!solutions_data.is_bounded(5));
+
+    level_data.win_count = 3;
+    assert_eq!(solver::solve(&level_data, 5, 1000).solutions_count, 0);
+}
+
+#[test]
+fn generated_levels_are_solvable() {
+    let generator_data = GeneratorData {
+        seed: 42,
+        ..GeneratorData::default()
+    };
+    let generated_data = generator::generate_level(&generator_data).unwrap();
+    assert!(generator_data.seed <= generated_data.seed);
+    assert!(generated_data
+        .solutions_data
+        .is_bounded(generator_data.max_solutions));
+    let level_data = &generated_data.level_data;
+    assert_eq!(level_data.layouts.len(), generator_data.layouts_count);
+    assert_eq!(level_data.win_count, generator_data.win_count);
+    assert!(level_data.par_time.is_some());
+
+    // the same seed gives the same level
+    let generated_again = generator::generate_level(&generator_data).unwrap();
+    assert_eq!(generated_again.seed, generated_data.seed);
+    assert_eq!(generated_again.level_data.layouts, level_data.layouts);
+    let solutions_data = solver::solve(level_data, generator_data.max_solutions, 100_000);
+    assert_eq!(
+        solutions_data.solutions_count,
+        generated_data.solutions_data.solutions_count
+    );
+}
+
+#[test]
+fn layouts_have_all_points() {
+    let generator_data = GeneratorData {
+        layouts_count: 3,
+        size: (5, 3),
+        pairs_count: 10,
+        obstacle_density: 0.5,
+        ..GeneratorData::default()
+    };
+    let level_data = generator::generate_layouts(&generator_data, 7);
+    let text: String = level_data.layouts.concat();
+    assert_eq!(text.matches('s').count(), 1);
+    assert_eq!(text.matches('f').count(), 1);
+    for digit in '0'..='9' {
+        assert_eq!(text.matches(digit).count(), 2);
+    }
+    for layout in &level_data.layouts {
+        let lines: Vec<&str> = layout.split('\n').collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines.iter().all(|line| line.len() == 5));
+    }
+}
+
+#[test]
+fn wrong_parameters() {
+    let too_many_pairs = GeneratorData {
+        pairs_count: 11,
+        ..GeneratorData::default()
+    };
+    assert!(generator::generate_level(&too_many_pairs).is_err());
+    let too_small = GeneratorData {
+        layouts_count: 1,
+        size: (2, 2),
+        ..GeneratorData::default()
+    };
+    assert!(too_small.check().is_err());
+    let too_few_pairs = GeneratorData {
+        win_count: 5,
+        ..GeneratorData::default()
+    };
+    assert!(too_few_pairs.check().is_err());
+    assert!(GeneratorData::default().check().is_ok());
+}
+
+#[test]
+fn export() {
+    let text = generator::export_levels(vec![common::make_portal_level()]).unwrap();
+    let pack_file_data: PackFileData = ron::de::from_str(&text).unwrap();
+    assert_eq!(pack_file_data.levels.len(), 1);
+    assert_eq!(pack_file_data.levels[0].name, "Test");
+    assert_eq!(
+        pack_file_data.levels[0].layouts,
+        common::make_portal_level().layouts
+    );
+}
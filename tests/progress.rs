@@ -0,0 +1,60 @@
+use gmtk_jam_2021::game_data::GameData;
+use gmtk_jam_2021::progress::{Grade, ProgressData};
+
+#[test]
+fn grades() {
+    assert_eq!(Grade::from_time(5.0, 10.0), Some(Grade::Gold));
+    assert_eq!(Grade::from_time(10.0, 10.0), Some(Grade::Gold));
+    assert_eq!(Grade::from_time(15.0, 10.0), Some(Grade::Silver));
+    assert_eq!(Grade::from_time(15.1, 10.0), Some(Grade::Bronze));
+    assert_eq!(Grade::from_time(20.0, 10.0), Some(Grade::Bronze));
+    assert_eq!(Grade::from_time(20.1, 10.0), None);
+}
+
+#[test]
+fn best_time_and_grade() {
+    let mut progress_data = ProgressData::default();
+    assert!(progress_data.record_time("Test", 12.0, Some(Grade::Silver)));
+    // a slower time keeps the best time, the grade only goes up
+    assert!(!progress_data.record_time("Test", 30.0, None));
+    let level_progress = progress_data.get_level_progress("Test").unwrap();
+    assert_eq!(level_progress.best_time, Some(12.0));
+    assert_eq!(level_progress.best_grade, Some(Grade::Silver));
+
+    assert!(progress_data.record_time("Test", 8.0, Some(Grade::Gold)));
+    assert!(!progress_data.record_time("Test", 8.0, Some(Grade::Gold)));
+    let level_progress = progress_data.get_level_progress("Test").unwrap();
+    assert_eq!(level_progress.best_time, Some(8.0));
+    assert_eq!(level_progress.best_grade, Some(Grade::Gold));
+    // the time does not complete the level
+    assert!(!progress_data.is_level_completed("Test"));
+    assert!(progress_data.get_level_progress("Other").is_none());
+}
+
+// chapters of tests/data/manifest.data:
+// 0 - the main file levels, always open, 1 - "Earlier" after one level,
+// 2 - "Later" after "Earlier", 3 - "Lost" after the pack "Missing" that does not exist
+#[test]
+fn unlock_requirements() {
+    let game_data = GameData::load_from_file_blocking("tests/data/manifest.data").unwrap();
+    let get_unlocked = |progress_data: &ProgressData| -> Vec<bool> {
+        (0..game_data.chapters.len())
+            .map(|chapter_index| progress_data.is_chapter_unlocked(&game_data, chapter_index))
+            .collect()
+    };
+    let mut progress_data = ProgressData::default();
+    assert_eq!(
+        get_unlocked(&progress_data),
+        vec![true, false, false, false]
+    );
+    assert!(progress_data.is_level_unlocked(&game_data, 0));
+    assert!(!progress_data.is_level_unlocked(&game_data, 1));
+
+    progress_data.complete_level("Main Level", 1);
+    assert_eq!(get_unlocked(&progress_data), vec![true, true, false, false]);
+    assert!(progress_data.is_level_unlocked(&game_data, 1));
+
+    // every pack shares the same level, so completing it completes "Earlier"
+    progress_data.complete_level("Pack Level", 1);
+    assert_eq!(get_unlocked(&progress_data), vec![true, true, true, false]);
+}
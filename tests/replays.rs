@@ -0,0 +1,63 @@
+use gmtk_jam_2021::game_data::{ConnectionData, GameData, LevelAdditionalData};
+use gmtk_jam_2021::replay::{ReplayData, REPLAY_FILE_EXTENSION};
+use gmtk_jam_2021::simulation;
+use gmtk_jam_2021::spatial_grid::ConnectionsGrid;
+
+// the recorded solutions still win with the current game data and logic
+#[test]
+fn replays_win() {
+    let game_data = GameData::load_from_file_blocking("assets/game.data").unwrap();
+    let mut replays_count = 0;
+    for entry in std::fs::read_dir("tests/replays").unwrap() {
+        let path = entry.unwrap().path();
+        if path.extension().and_then(|extension| extension.to_str()) != Some(REPLAY_FILE_EXTENSION)
+        {
+            continue;
+        }
+        let file_name = path.to_str().unwrap();
+        let replay_data = ReplayData::load_from_file(file_name).unwrap();
+        let level_index = replay_data.find_level_index(&game_data).unwrap();
+        let level_data = &game_data.levels[level_index];
+
+        let level_additional_data = LevelAdditionalData::new(level_data);
+        let mut connections_data = Vec::<ConnectionData>::new();
+        let mut connections_grid = ConnectionsGrid::default();
+        for (index, action) in replay_data.actions.iter().enumerate() {
+            assert!(
+                simulation::simulate_action(
+                    level_data,
+                    &level_additional_data,
+                    &mut connections_data,
+                    &mut connections_grid,
+                    &action.action_type,
+                ),
+                "{}: action {} rejected",
+                file_name,
+                index + 1
+            );
+        }
+        assert!(
+            simulation::is_win(&level_additional_data, &connections_data),
+            "{}: not won",
+            file_name
+        );
+        replays_count += 1;
+    }
+    assert!(0 < replays_count);
+}
+
+#[test]
+fn replay_level_by_name() {
+    let game_data = GameData::load_from_file_blocking("tests/data/manifest.data").unwrap();
+    let mut replay_data = ReplayData::new(0, "Pack Level");
+    // "Pack Level" is at 1, 2 and 3, the recorded index is kept when it fits
+    assert_eq!(replay_data.find_level_index(&game_data), Some(1));
+    replay_data.level_index = 2;
+    assert_eq!(replay_data.find_level_index(&game_data), Some(2));
+    replay_data.level_index = 10;
+    assert_eq!(replay_data.find_level_index(&game_data), Some(1));
+    assert_eq!(
+        ReplayData::new(0, "Removed Level").find_level_index(&game_data),
+        None
+    );
+}
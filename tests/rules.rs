@@ -0,0 +1,211 @@
+mod common;
+
+use gmtk_jam_2021::game_data::{LevelAdditionalData, LevelData};
+use gmtk_jam_2021::replay::ReplayActionType;
+use gmtk_jam_2021::rules::{self, RejectionReason};
+use gmtk_jam_2021::simulation;
+use gmtk_jam_2021::spatial_grid::ConnectionsGrid;
+
+use common::connect;
+
+#[test]
+fn point_used_only_in_its_layout() {
+    let level_additional_data = LevelAdditionalData::new(&common::make_portal_level());
+    let mut connections_data = Vec::new();
+    connect(&level_additional_data, &mut connections_data, 0, 2, 0);
+
+    assert!(rules::is_point_used(
+        &level_additional_data,
+        &connections_data,
+        0,
+        0
+    ));
+    assert!(rules::is_point_used(
+        &level_additional_data,
+        &connections_data,
+        0,
+        2
+    ));
+    // the same point index in another layout is not touched
+    assert!(!rules::is_point_used(
+        &level_additional_data,
+        &connections_data,
+        1,
+        0
+    ));
+    // the partner of the portal is used by the jump
+    assert!(rules::is_point_used(
+        &level_additional_data,
+        &connections_data,
+        1,
+        1
+    ));
+    assert!(!rules::is_point_used(
+        &level_additional_data,
+        &connections_data,
+        0,
+        1
+    ));
+}
+
+#[test]
+fn next_start_is_portal_partner() {
+    let level_additional_data = LevelAdditionalData::new(&common::make_portal_level());
+    let mut connections_data = Vec::new();
+    connect(&level_additional_data, &mut connections_data, 0, 2, 0);
+    connect(&level_additional_data, &mut connections_data, 1, 1, 0);
+
+    let next_start = rules::get_next_start(&level_additional_data, &connections_data[1]).unwrap();
+    assert_eq!(next_start.layout_index, 0);
+    assert_eq!(next_start.point_index, 1);
+    assert_eq!(
+        level_additional_data.get_current_layout_index(&connections_data),
+        0
+    );
+
+    connect(&level_additional_data, &mut connections_data, 0, 1, 3);
+    assert_eq!(
+        rules::get_next_start(&level_additional_data, &connections_data[2]),
+        None
+    );
+}
+
+#[test]
+fn finish_only_at_the_end() {
+    let level_data = common::make_portal_level();
+    let level_additional_data = LevelAdditionalData::new(&level_data);
+    let mut connections_data = Vec::new();
+    connect(&level_additional_data, &mut connections_data, 0, 2, 0);
+
+    assert_eq!(
+        rules::check_target(&level_data, &level_additional_data, &connections_data, 0, 3),
+        Err(RejectionReason::NotEnoughConnections {
+            connections_count: 1,
+            win_count: 2,
+        })
+    );
+
+    connect(&level_additional_data, &mut connections_data, 1, 1, 0);
+    assert_eq!(
+        rules::check_target(&level_data, &level_additional_data, &connections_data, 0, 3),
+        Ok(())
+    );
+}
+
+#[test]
+fn used_point_rejected() {
+    let level_data = common::make_portal_level();
+    let level_additional_data = LevelAdditionalData::new(&level_data);
+    let mut connections_data = Vec::new();
+    connect(&level_additional_data, &mut connections_data, 0, 2, 0);
+    connect(&level_additional_data, &mut connections_data, 1, 1, 0);
+
+    assert_eq!(
+        rules::check_target(&level_data, &level_additional_data, &connections_data, 0, 0),
+        Err(RejectionReason::PointUsed)
+    );
+    assert_eq!(
+        rules::check_target(&level_data, &level_additional_data, &connections_data, 1, 1),
+        Err(RejectionReason::PointUsed)
+    );
+}
+
+#[test]
+fn exact_count() {
+    let level_data = LevelData {
+        is_exact_count: true,
+        ..common::make_portal_level()
+    };
+    let level_additional_data = LevelAdditionalData::new(&level_data);
+    let mut connections_data = Vec::new();
+    connect(&level_additional_data, &mut connections_data, 0, 2, 0);
+    connect(&level_additional_data, &mut connections_data, 1, 1, 0);
+
+    // only the finish may follow the last needed connection
+    assert_eq!(
+        rules::check_target(&level_data, &level_additional_data, &connections_data, 0, 0),
+        Err(RejectionReason::OnlyFinishLeft)
+    );
+    assert_eq!(
+        rules::check_target(&level_data, &level_additional_data, &connections_data, 0, 3),
+        Ok(())
+    );
+
+    connect(&level_additional_data, &mut connections_data, 0, 1, 0);
+    assert_eq!(
+        rules::check_target(&level_data, &level_additional_data, &connections_data, 0, 3),
+        Err(RejectionReason::TooManyConnections {
+            connections_count: 3,
+            win_count: 2,
+        })
+    );
+}
+
+#[test]
+fn out_of_moves() {
+    let level_data = LevelData {
+        max_moves: Some(1),
+        ..common::make_portal_level()
+    };
+    let level_additional_data = LevelAdditionalData::new(&level_data);
+    let mut connections_data = Vec::new();
+    assert_eq!(rules::get_moves_left(&level_data, 0), Some(1));
+
+    connect(&level_additional_data, &mut connections_data, 0, 2, 0);
+    assert_eq!(rules::get_moves_left(&level_data, 1), Some(0));
+    assert_eq!(
+        rules::check_target(&level_data, &level_additional_data, &connections_data, 1, 0),
+        Err(RejectionReason::OutOfMoves)
+    );
+    assert_eq!(rules::get_moves_left(&common::make_portal_level(), 5), None);
+}
+
+#[test]
+fn full_path_wins() {
+    let level_data = common::make_portal_level();
+    let level_additional_data = LevelAdditionalData::new(&level_data);
+    let mut connections_data = Vec::new();
+    let mut connections_grid = ConnectionsGrid::default();
+    let actions = [(0, 2, 0), (1, 1, 0), (0, 1, 3)];
+    for &(layout_index, from_point_index, to_point_index) in actions.iter() {
+        assert!(!simulation::is_win(
+            &level_additional_data,
+            &connections_data
+        ));
+        assert!(simulation::simulate_action(
+            &level_data,
+            &level_additional_data,
+            &mut connections_data,
+            &mut connections_grid,
+            &ReplayActionType::Connect {
+                layout_index,
+                from_point_index,
+                to_point_index,
+            },
+        ));
+    }
+    assert!(simulation::is_win(
+        &level_additional_data,
+        &connections_data
+    ));
+}
+
+#[test]
+fn finish_too_early_is_not_simulated() {
+    let level_data = common::make_portal_level();
+    let level_additional_data = LevelAdditionalData::new(&level_data);
+    let mut connections_data = Vec::new();
+    let mut connections_grid = ConnectionsGrid::default();
+    assert!(!simulation::simulate_action(
+        &level_data,
+        &level_additional_data,
+        &mut connections_data,
+        &mut connections_grid,
+        &ReplayActionType::Connect {
+            layout_index: 0,
+            from_point_index: 2,
+            to_point_index: 3,
+        },
+    ));
+    assert!(connections_data.is_empty());
+}
@@ -0,0 +1,40 @@
+mod common;
+
+use gmtk_jam_2021::game_data::LevelAdditionalData;
+use gmtk_jam_2021::score::{ScoreData, BASE_SCORE};
+
+use common::connect;
+
+#[test]
+fn solution_score() {
+    let level_additional_data = LevelAdditionalData::new(&common::make_portal_level());
+    let mut connections_data = Vec::new();
+    let score_data = ScoreData::new(&level_additional_data, &connections_data, 0);
+    assert_eq!(score_data.get_penalties(), [0.0; 4]);
+    assert_eq!(score_data.get_score(), BASE_SCORE as u32);
+
+    // s -> 0 and 1 -> 1 end at points paired with the other layout, 1 -> f does not
+    connect(&level_additional_data, &mut connections_data, 0, 2, 0);
+    connect(&level_additional_data, &mut connections_data, 1, 1, 0);
+    connect(&level_additional_data, &mut connections_data, 0, 1, 3);
+    let score_data = ScoreData::new(&level_additional_data, &connections_data, 1);
+    assert!((score_data.length - 5.0).abs() < 0.001);
+    assert_eq!(score_data.connections_count, 3);
+    assert_eq!(score_data.portal_jumps_count, 2);
+    assert_eq!(score_data.undos_count, 1);
+    let penalties = score_data.get_penalties();
+    assert!((penalties[0] - 50.0).abs() < 0.001);
+    assert_eq!(penalties[1..], [75.0, 30.0, 20.0]);
+    assert_eq!(score_data.get_score(), 825);
+}
+
+#[test]
+fn score_not_below_zero() {
+    let score_data = ScoreData {
+        length: 20.0,
+        connections_count: 10,
+        portal_jumps_count: 5,
+        undos_count: 100,
+    };
+    assert_eq!(score_data.get_score(), 0);
+}
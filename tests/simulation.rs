@@ -0,0 +1,38 @@
+mod common;
+
+use gmtk_jam_2021::game_data::LevelAdditionalData;
+use gmtk_jam_2021::simulation;
+
+use common::connect;
+
+// the connections of the solution of the portal level, restored on its changed versions
+#[test]
+fn restore_after_change() {
+    let level_data = common::make_portal_level();
+    let level_additional_data = LevelAdditionalData::new(&level_data);
+    let mut connections_data = Vec::new();
+    connect(&level_additional_data, &mut connections_data, 0, 2, 0);
+    connect(&level_additional_data, &mut connections_data, 1, 1, 0);
+    connect(&level_additional_data, &mut connections_data, 0, 1, 3);
+    let restore = |layouts: &[&str]| {
+        let new_level_data = common::make_level(2, layouts);
+        let new_level_additional_data = LevelAdditionalData::new(&new_level_data);
+        simulation::restore_connections(
+            &new_level_data,
+            &new_level_additional_data,
+            &connections_data,
+        )
+    };
+
+    let restored_data = restore(&["s0. f.1", "1.0"]);
+    assert_eq!(restored_data.len(), 3);
+    assert!(simulation::is_win(&level_additional_data, &restored_data));
+
+    // an obstacle between the points of layout 1 stops at the second connection
+    let restored_data = restore(&["s0. f.1", "1z0"]);
+    assert_eq!(restored_data.len(), 1);
+    assert_eq!(restored_data[0].to_point_index, 0);
+
+    // the first connection is blocked, nothing is kept
+    assert!(restore(&["sz0 f.1", "1.0"]).is_empty());
+}
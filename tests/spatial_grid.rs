@@ -0,0 +1,81 @@
+mod common;
+
+use gmtk_jam_2021::game_data::{ConnectionData, LevelAdditionalData};
+use gmtk_jam_2021::spatial_grid::{ConnectionsGrid, SpatialGrid};
+use macroquad::prelude::*;
+
+#[test]
+fn grid_indices() {
+    let mut grid = SpatialGrid::default();
+    grid.insert(0, vec2(0.0, 0.0), vec2(0.0, 0.0));
+    grid.insert(1, vec2(-10.0, -10.0), vec2(-9.0, -9.0));
+    grid.insert(2, vec2(0.0, 0.0), vec2(20.0, 1.0));
+
+    assert_eq!(grid.get_indices(vec2(0.5, 0.5), vec2(1.0, 1.0)), vec![0, 2]);
+    assert_eq!(grid.get_indices(vec2(18.0, 0.0), vec2(19.0, 0.0)), vec![2]);
+    assert_eq!(
+        grid.get_indices(vec2(-10.0, -10.0), vec2(0.0, 0.0)),
+        vec![0, 1, 2]
+    );
+    assert!(grid
+        .get_indices(vec2(50.0, 50.0), vec2(60.0, 60.0))
+        .is_empty());
+
+    grid.remove(2, vec2(0.0, 0.0), vec2(20.0, 1.0));
+    assert_eq!(grid.get_indices(vec2(0.5, 0.5), vec2(1.0, 1.0)), vec![0]);
+    assert!(grid
+        .get_indices(vec2(18.0, 0.0), vec2(19.0, 0.0))
+        .is_empty());
+}
+
+#[test]
+fn connections_follow_path() {
+    // two layouts with a row of five points 9 cells apart each
+    let level_additional_data = LevelAdditionalData::new(&common::make_level(
+        1,
+        &[
+            "0........1........2........3........4",
+            "5........6........7........8........9",
+        ],
+    ));
+    let connect = |layout_index, from_point_index, to_point_index| {
+        ConnectionData::new(
+            &level_additional_data.layouts_data[layout_index],
+            layout_index,
+            from_point_index,
+            to_point_index,
+        )
+    };
+    let mut connections_data = vec![connect(0, 0, 1), connect(1, 3, 4), connect(0, 3, 4)];
+    let mut connections_grid = ConnectionsGrid::new(&connections_data);
+    assert_eq!(
+        connections_grid.get_indices(0, vec2(0.0, 0.0), vec2(40.0, 0.0)),
+        vec![0, 2]
+    );
+    assert_eq!(
+        connections_grid.get_indices(1, vec2(30.0, 0.0), vec2(31.0, 0.0)),
+        vec![1]
+    );
+
+    // undo and connect elsewhere, the same index moves to another place
+    connections_data.truncate(2);
+    connections_data.push(connect(0, 1, 2));
+    connections_grid.update(&connections_data);
+    assert_eq!(
+        connections_grid.get_indices(0, vec2(30.0, 0.0), vec2(31.0, 0.0)),
+        Vec::<usize>::new()
+    );
+    assert_eq!(
+        connections_grid.get_indices(0, vec2(12.0, 0.0), vec2(13.0, 0.0)),
+        vec![2]
+    );
+
+    connections_data.clear();
+    connections_grid.update(&connections_data);
+    assert!(connections_grid
+        .get_indices(0, vec2(0.0, 0.0), vec2(40.0, 0.0))
+        .is_empty());
+    assert!(connections_grid
+        .get_indices(2, vec2(0.0, 0.0), vec2(40.0, 0.0))
+        .is_empty());
+}